@@ -0,0 +1,19 @@
+//! The Cornell box from _Ray Tracing: The Next Week_, built by the library and rendered smaller than the renderer
+//! would.
+//!
+//! `cargo run --release --example cornell_box > cornell_box.ppm`
+
+use std::sync::Arc;
+
+use ray_tracing_in_one_weekend::{render, RenderSettings, Scene};
+use ray_tracing_in_one_weekend::scenes;
+
+fn main() {
+    let scene = Scene {
+        settings: RenderSettings { image_width: 400, image_height: 400, samples_per_pixel: 100, ..Default::default() },
+        ..scenes::cornell_box()
+    };
+    let image = render(&scene, Arc::new(scene.perspective_camera(0.0, 1.0)), &scene.settings);
+    image.write(&mut std::io::stdout());
+    eprintln!("\nDone.");
+}
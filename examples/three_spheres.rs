@@ -0,0 +1,21 @@
+//! Matte, glass and metal spheres on a checkered ground, with depth of field, rendered through the library with a
+//! Halton sampler.
+//!
+//! `cargo run --release --example three_spheres > three_spheres.png`
+
+use std::io::BufWriter;
+use std::sync::Arc;
+
+use ray_tracing_in_one_weekend::{render, RenderSettings, Scene};
+use ray_tracing_in_one_weekend::sampler::SamplerKind;
+use ray_tracing_in_one_weekend::scenes;
+
+fn main() {
+    let scene = Scene {
+        settings: RenderSettings { image_width: 640, image_height: 360, samples_per_pixel: 64, sampler: SamplerKind::Halton, ..Default::default() },
+        ..scenes::three_spheres()
+    };
+    let image = render(&scene, Arc::new(scene.perspective_camera(0.0, 1.0)), &scene.settings);
+    image.write_png(&mut BufWriter::new(std::io::stdout())).expect("Could not write the image!");
+    eprintln!("\nDone.");
+}
@@ -0,0 +1,82 @@
+use crate::vec3::{Point3, Vec3};
+
+/// Everything about the camera that can be animated, at a point in time.
+#[derive(Clone)]
+pub struct CameraKeyframe {
+    pub time: f64,
+    pub lookfrom: Point3,
+    pub lookat: Point3,
+    pub vfov: f64,
+    pub aperture: f64,
+    pub focus_dist: f64,
+}
+
+/// Camera keyframes, linearly interpolated. Before the first and after the last keyframe the camera holds still.
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    /// Panics if `keyframes` is empty.
+    pub fn new(keyframes: Vec<CameraKeyframe>) -> Self {
+        assert!(!keyframes.is_empty(), "CameraPath needs at least one keyframe!");
+        let mut keyframes = keyframes;
+        keyframes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        Self { keyframes }
+    }
+
+    /// A path that never moves, for scenes without camera animation.
+    pub fn fixed(lookfrom: Point3, lookat: Point3, vfov: f64, aperture: f64, focus_dist: f64) -> Self {
+        Self::new(vec![CameraKeyframe { time: 0.0, lookfrom, lookat, vfov, aperture, focus_dist }])
+    }
+
+    pub fn at(&self, time: f64) -> CameraKeyframe {
+        let first = self.keyframes.first().unwrap();
+        let last = self.keyframes.last().unwrap();
+        if time <= first.time {
+            return CameraKeyframe { time, ..first.clone() };
+        }
+        if time >= last.time {
+            return CameraKeyframe { time, ..last.clone() };
+        }
+        let next_index = self.keyframes.iter().position(|keyframe| keyframe.time > time).unwrap();
+        let previous = &self.keyframes[next_index - 1];
+        let next = &self.keyframes[next_index];
+        let t = (time - previous.time) / (next.time - previous.time);
+        let lerp = |a: f64, b: f64| a + t * (b - a);
+        let lerp_vec = |a: &Vec3, b: &Vec3| a + t * (b - a);
+        CameraKeyframe {
+            time,
+            lookfrom: lerp_vec(&previous.lookfrom, &next.lookfrom),
+            lookat: lerp_vec(&previous.lookat, &next.lookat),
+            vfov: lerp(previous.vfov, next.vfov),
+            aperture: lerp(previous.aperture, next.aperture),
+            focus_dist: lerp(previous.focus_dist, next.focus_dist),
+        }
+    }
+}
+
+/// Timing of an image sequence. Frame `n` starts at `n / frames_per_second` and the shutter stays open
+/// for `shutter_angle / 360` of a frame, as on a film camera.
+pub struct FrameTiming {
+    pub frames_per_second: f64,
+    pub shutter_angle: f64,
+}
+
+impl FrameTiming {
+    /// The `(time_start, time_end)` shutter window of a zero-based frame.
+    pub fn shutter_window(&self, frame: u32) -> (f64, f64) {
+        let frame_start = frame as f64 / self.frames_per_second;
+        (frame_start, frame_start + (self.shutter_angle / 360.0) / self.frames_per_second)
+    }
+
+    /// The `(time_start, time_end)` window covering the shutter windows of the first `frames` frames.
+    pub fn sequence_window(&self, frames: u32) -> (f64, f64) {
+        (self.shutter_window(0).0, self.shutter_window(frames.max(1) - 1).1)
+    }
+
+    /// File name for a zero-based frame. Frames are numbered from one, like most compositing tools expect.
+    pub fn file_name(frame: u32) -> String {
+        format!("frame_{:04}.png", frame + 1)
+    }
+}
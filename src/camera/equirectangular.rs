@@ -0,0 +1,57 @@
+use std::f64::consts;
+
+use super::{camera_basis, Camera, Shutter};
+use crate::ray::Ray;
+use crate::spectrum::Wavelengths;
+use crate::sampler::Sampler;
+use crate::vec3::{Point3, Vec3};
+
+/// Full spherical 360° panorama in the latitude/longitude layout most viewers expect.
+/// The image should have a 2:1 aspect ratio; `lookat` ends up in the centre and `vup` at the top edge.
+pub struct EquirectangularCamera {
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    time_start: f64,
+    time_end: f64,
+    shutter: Shutter,
+}
+
+impl EquirectangularCamera {
+    pub fn new(lookfrom: Point3, lookat: Point3, vup: Vec3, time_start: f64, time_end: f64) -> EquirectangularCamera {
+        let (u, v, w) = camera_basis(&lookfrom, &lookat, &vup);
+        EquirectangularCamera {
+            origin: lookfrom,
+            u,
+            v,
+            w,
+            time_start,
+            time_end,
+            shutter: Shutter::default(),
+        }
+    }
+
+    pub fn with_shutter(self, shutter: Shutter) -> EquirectangularCamera {
+        EquirectangularCamera {
+            shutter,
+            ..self
+        }
+    }
+}
+
+impl Camera for EquirectangularCamera {
+    fn get_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+        let longitude = (s - 0.5) * 2.0 * consts::PI;
+        let latitude = (t - 0.5) * consts::PI;
+        let (sin_longitude, cos_longitude) = longitude.sin_cos();
+        let (sin_latitude, cos_latitude) = latitude.sin_cos();
+        let direction = cos_latitude * sin_longitude * &self.u + sin_latitude * &self.v - cos_latitude * cos_longitude * &self.w;
+        Some(Ray {
+            origin: self.origin.clone(),
+            direction,
+            time: self.shutter.sample_time(self.time_start, self.time_end, t, sampler.get_1d()),
+            wavelengths: Wavelengths::Rgb,
+        })
+    }
+}
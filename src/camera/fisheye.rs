@@ -0,0 +1,86 @@
+use super::{camera_basis, Camera, Shutter};
+use crate::ray::Ray;
+use crate::spectrum::Wavelengths;
+use crate::sampler::Sampler;
+use crate::util::degrees_to_radians;
+use crate::vec3::{Point3, Vec3};
+
+/// How distance from the centre of a fisheye image maps to the angle from the optical axis.
+#[derive(Clone, Copy)]
+pub enum FisheyeMapping {
+    /// Distance proportional to angle, `r = f * theta`.
+    Equidistant,
+    /// Equal-area, `r = 2f * sin(theta / 2)`.
+    Equisolid,
+}
+
+/// A circular fisheye: the image circle touches the top and bottom of the frame and covers `fov` degrees.
+/// Pixels outside the circle get no ray.
+pub struct FisheyeCamera {
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    half_fov: f64,
+    aspect_ratio: f64,
+    mapping: FisheyeMapping,
+    time_start: f64,
+    time_end: f64,
+    shutter: Shutter,
+}
+
+impl FisheyeCamera {
+    /// `fov` is in degrees and may be up to 360.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(lookfrom: Point3, lookat: Point3, vup: Vec3, fov: f64, aspect_ratio: f64, mapping: FisheyeMapping, time_start: f64, time_end: f64) -> FisheyeCamera {
+        let (u, v, w) = camera_basis(&lookfrom, &lookat, &vup);
+        FisheyeCamera {
+            origin: lookfrom,
+            u,
+            v,
+            w,
+            half_fov: degrees_to_radians(fov.clamp(0.0, 360.0)) / 2.0,
+            aspect_ratio,
+            mapping,
+            time_start,
+            time_end,
+            shutter: Shutter::default(),
+        }
+    }
+
+    pub fn with_shutter(self, shutter: Shutter) -> FisheyeCamera {
+        FisheyeCamera {
+            shutter,
+            ..self
+        }
+    }
+
+    /// Angle from the optical axis for a normalized radius, where 1.0 is the edge of the image circle.
+    fn theta(&self, radius: f64) -> f64 {
+        match self.mapping {
+            FisheyeMapping::Equidistant => radius * self.half_fov,
+            FisheyeMapping::Equisolid => 2.0 * (radius * (self.half_fov / 2.0).sin()).clamp(-1.0, 1.0).asin(),
+        }
+    }
+}
+
+impl Camera for FisheyeCamera {
+    fn get_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+        let x = (2.0 * s - 1.0) * self.aspect_ratio;
+        let y = 2.0 * t - 1.0;
+        let radius = (x * x + y * y).sqrt();
+        if radius > 1.0 {
+            return None;
+        }
+        let theta = self.theta(radius);
+        let phi = y.atan2(x);
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let direction = sin_theta * phi.cos() * &self.u + sin_theta * phi.sin() * &self.v - cos_theta * &self.w;
+        Some(Ray {
+            origin: self.origin.clone(),
+            direction,
+            time: self.shutter.sample_time(self.time_start, self.time_end, t, sampler.get_1d()),
+            wavelengths: Wavelengths::Rgb,
+        })
+    }
+}
@@ -0,0 +1,185 @@
+use std::f64::consts;
+use std::sync::Arc;
+
+use super::camera_basis;
+use crate::hittable::Hittable;
+use crate::ray::Ray;
+use crate::spectrum::Wavelengths;
+use crate::sampler::{IndependentSampler, Sampler};
+use crate::texture::Texture;
+use crate::util::degrees_to_radians;
+use crate::vec3::{Point3, Vec3};
+
+// Texels along each side of the grid an aperture mask is tabulated on.
+const MASK_RESOLUTION: usize = 256;
+
+/// Shape of the lens opening, which is also the shape out-of-focus highlights take.
+#[derive(Clone)]
+pub enum ApertureShape {
+    Circle,
+    /// A regular polygon, as formed by the blades of a real iris. `rotation` is in degrees.
+    Polygon { blades: u32, rotation: f64 },
+    /// A texture over the square around the unit disk; brighter texels let more light through.
+    Mask(Arc<ApertureMask>),
+}
+
+impl ApertureShape {
+    /// A point on the aperture with `x` and `y` in `[-1.0, 1.0]`, like `Vec3::sample_unit_disk`.
+    pub fn sample(&self, sampler: &mut dyn Sampler) -> Vec3 {
+        match self {
+            ApertureShape::Circle => Vec3::sample_unit_disk(sampler.get_2d()),
+            ApertureShape::Polygon { blades, rotation } => {
+                let blades = (*blades).max(3);
+                // Pick one of the triangles fanning out from the centre, then a uniform point inside it.
+                let wedge = degrees_to_radians(360.0) / blades as f64;
+                let start = degrees_to_radians(*rotation) + wedge * (sampler.get_1d() * blades as f64).floor();
+                let (mut a, mut b) = sampler.get_2d();
+                if a + b > 1.0 {
+                    a = 1.0 - a;
+                    b = 1.0 - b;
+                }
+                let (sin_start, cos_start) = start.sin_cos();
+                let (sin_end, cos_end) = (start + wedge).sin_cos();
+                Vec3 { x: a * cos_start + b * cos_end, y: a * sin_start + b * sin_end, z: 0.0 }
+            },
+            ApertureShape::Mask(mask) => mask.sample(sampler.get_2d()),
+        }
+    }
+}
+
+/// An aperture mask texture, tabulated on a grid so points can be drawn in proportion to the light let through
+/// with a single 2D sample.
+pub struct ApertureMask {
+    // Cumulative distributions over the rows of the grid, from the bottom, and over the texels of each row, with
+    // one more entry than there are rows or texels.
+    row_cdf: Vec<f64>,
+    texel_cdfs: Vec<Vec<f64>>,
+}
+
+impl ApertureMask {
+    /// Reads `texture` with `Texture::scalar`, `u` and `v` going from 0 to 1 across the square. Panics if it
+    /// lets no light through at all.
+    pub fn new(texture: &dyn Texture) -> Self {
+        let texel_cdfs: Vec<Vec<f64>> = (0..MASK_RESOLUTION).map(|row| {
+            Self::build_cdf((0..MASK_RESOLUTION).map(|column| {
+                let (u, v) = ((column as f64 + 0.5) / MASK_RESOLUTION as f64, (row as f64 + 0.5) / MASK_RESOLUTION as f64);
+                texture.scalar(u, v, &Vec3 { x: 2.0 * u - 1.0, y: 2.0 * v - 1.0, z: 0.0 }).max(0.0)
+            }))
+        }).collect();
+        let row_cdf = Self::build_cdf(texel_cdfs.iter().map(|cdf| *cdf.last().unwrap()));
+        assert!(*row_cdf.last().unwrap() > 0.0, "Aperture mask must let some light through!");
+        Self { row_cdf: Self::normalize(row_cdf), texel_cdfs: texel_cdfs.into_iter().map(Self::normalize).collect() }
+    }
+
+    fn sample(&self, (x, y): (f64, f64)) -> Vec3 {
+        let (row, y) = Self::sample_cdf(&self.row_cdf, y);
+        let (column, x) = Self::sample_cdf(&self.texel_cdfs[row], x);
+        let u = (column as f64 + x) / MASK_RESOLUTION as f64;
+        let v = (row as f64 + y) / MASK_RESOLUTION as f64;
+        Vec3 { x: 2.0 * u - 1.0, y: 2.0 * v - 1.0, z: 0.0 }
+    }
+
+    // Unnormalized, so the total of each row is still there to build the distribution over rows from.
+    fn build_cdf(weights: impl Iterator<Item = f64>) -> Vec<f64> {
+        let mut cdf = vec![0.0];
+        for weight in weights {
+            cdf.push(cdf.last().unwrap() + weight);
+        }
+        cdf
+    }
+
+    fn normalize(mut cdf: Vec<f64>) -> Vec<f64> {
+        let total = *cdf.last().unwrap();
+        for (i, value) in cdf.iter_mut().enumerate() {
+            // Rows that let nothing through are never picked, but stay valid distributions all the same.
+            *value = if total > 0.0 { *value / total } else { i as f64 / MASK_RESOLUTION as f64 };
+        }
+        cdf
+    }
+
+    // The entry `sample` falls in, and where in it, from 0 to 1. Entries with nothing in them are never picked.
+    fn sample_cdf(cdf: &[f64], sample: f64) -> (usize, f64) {
+        let index = cdf.partition_point(|&value| value <= sample).clamp(1, cdf.len() - 1) - 1;
+        let width = cdf[index + 1] - cdf[index];
+        (index, if width > 0.0 { ((sample - cdf[index]) / width).clamp(0.0, 1.0) } else { 0.5 })
+    }
+}
+
+/// Describes a lens the way a photographer would, for cameras that take a field of view and an aperture diameter.
+pub struct Lens {
+    /// Focal length in millimetres.
+    pub focal_length: f64,
+    pub f_stop: f64,
+    /// Height of the sensor or film in millimetres; 24 for full frame.
+    pub sensor_height: f64,
+}
+
+impl Lens {
+    pub fn new(focal_length: f64, f_stop: f64) -> Self {
+        Self {
+            focal_length,
+            f_stop,
+            sensor_height: 24.0,
+        }
+    }
+
+    /// The lens that gives a vertical field of view of `vfov` degrees on a full frame sensor.
+    pub fn from_vfov(vfov: f64, f_stop: f64) -> Self {
+        let sensor_height = 24.0;
+        Self {
+            focal_length: sensor_height / (2.0 * (degrees_to_radians(vfov) / 2.0).tan()),
+            f_stop,
+            sensor_height,
+        }
+    }
+
+    /// Vertical field of view in degrees.
+    pub fn vfov(&self) -> f64 {
+        2.0 * (self.sensor_height / (2.0 * self.focal_length)).atan() * 180.0 / consts::PI
+    }
+
+    /// Diameter of the entrance pupil in scene units, given how many scene units make up a metre.
+    pub fn aperture(&self, units_per_meter: f64) -> f64 {
+        self.focal_length / self.f_stop / 1000.0 * units_per_meter
+    }
+}
+
+/// Finds the focus distance that puts whatever is seen at `(s, t)` in focus, by tracing a ray through it
+/// from `lookfrom`. Returns `None` if the ray escapes the scene.
+#[allow(clippy::too_many_arguments)]
+pub fn autofocus(world: &dyn Hittable, lookfrom: &Point3, lookat: &Point3, vup: &Vec3, vfov: f64, aspect_ratio: f64, s: f64, t: f64, time: f64) -> Option<f64> {
+    let (u, v, w) = camera_basis(lookfrom, lookat, vup);
+    let viewport_height = 2.0 * (degrees_to_radians(vfov) / 2.0).tan();
+    let viewport_width = aspect_ratio * viewport_height;
+    let direction = (s - 0.5) * viewport_width * &u + (t - 0.5) * viewport_height * &v - &w;
+    let ray = Ray { origin: lookfrom.clone(), direction, time, wavelengths: Wavelengths::Rgb };
+    let hit_record = world.hit(&ray, 0.001, f64::INFINITY, &mut IndependentSampler::new(1, 0))?;
+    // The focus plane is parallel to the image plane, so measure along the view axis rather than the ray.
+    Some((&hit_record.p - lookfrom).dot(&-w))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Color;
+
+    // Open over the left quarter, and twice as open over its top half.
+    struct Slit;
+
+    impl Texture for Slit {
+        fn value(&self, u: f64, v: f64, _: &Point3) -> Color {
+            let open = if u >= 0.25 { 0.0 } else if v >= 0.5 { 1.0 } else { 0.5 };
+            Color { x: open, y: open, z: open }
+        }
+    }
+
+    #[test]
+    fn mask_samples_follow_the_light_let_through() {
+        let mask = ApertureMask::new(&Slit);
+        let count = 100;
+        let points: Vec<Vec3> = (0..count * count).map(|i| mask.sample(((i % count) as f64 / count as f64, (i / count) as f64 / count as f64))).collect();
+        assert!(points.iter().all(|p| p.x >= -1.0 && p.x <= -0.5 && p.y >= -1.0 && p.y <= 1.0));
+        let top = points.iter().filter(|p| p.y >= 0.0).count() as f64 / points.len() as f64;
+        assert!((top - 2.0 / 3.0).abs() < 0.01, "{} of the samples in the top half", top);
+    }
+}
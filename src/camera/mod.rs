@@ -0,0 +1,32 @@
+mod shutter;
+pub use shutter::{Shutter, ShutterCurve};
+mod lens;
+pub use lens::{autofocus, ApertureMask, ApertureShape, Lens};
+mod perspective;
+pub use perspective::PerspectiveCamera;
+mod orthographic;
+pub use orthographic::OrthographicCamera;
+mod fisheye;
+pub use fisheye::{FisheyeCamera, FisheyeMapping};
+mod equirectangular;
+pub use equirectangular::EquirectangularCamera;
+
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::vec3::{Point3, Vec3};
+
+pub trait Camera: Send + Sync {
+    /// Generates a ray through the image at `s` (0.0 on the left to 1.0 on the right) and `t` (0.0 at the bottom
+    /// to 1.0 at the top), or `None` where the projection does not cover the image, like outside a fisheye circle.
+    /// Lens and shutter samples come from `sampler`.
+    fn get_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray>;
+}
+
+/// The orthonormal basis shared by every camera: `w` points backwards, away from `lookat`, and `u` and `v` span
+/// the image plane.
+fn camera_basis(lookfrom: &Point3, lookat: &Point3, vup: &Vec3) -> (Vec3, Vec3, Vec3) {
+    let w = (lookfrom - lookat).unit_vector();
+    let u = vup.cross(&w).unit_vector();
+    let v = w.cross(&u);
+    (u, v, w)
+}
@@ -0,0 +1,53 @@
+use super::{camera_basis, Camera, Shutter};
+use crate::ray::Ray;
+use crate::spectrum::Wavelengths;
+use crate::sampler::Sampler;
+use crate::vec3::{Point3, Vec3};
+
+/// Parallel projection for technical and isometric renders. Every ray leaves the image plane
+/// through `lookfrom` in the same direction, so there is no perspective and no depth of field.
+pub struct OrthographicCamera {
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    direction: Vec3,
+    time_start: f64,
+    time_end: f64,
+    shutter: Shutter,
+}
+
+impl OrthographicCamera {
+    /// `viewport_height` is the height of the visible region in scene units.
+    pub fn new(lookfrom: Point3, lookat: Point3, vup: Vec3, viewport_height: f64, aspect_ratio: f64, time_start: f64, time_end: f64) -> OrthographicCamera {
+        let (u, v, w) = camera_basis(&lookfrom, &lookat, &vup);
+        let horizontal = aspect_ratio * viewport_height * &u;
+        let vertical = viewport_height * &v;
+        OrthographicCamera {
+            lower_left_corner: &lookfrom - &horizontal / 2.0 - &vertical / 2.0,
+            horizontal,
+            vertical,
+            direction: -w,
+            time_start,
+            time_end,
+            shutter: Shutter::default(),
+        }
+    }
+
+    pub fn with_shutter(self, shutter: Shutter) -> OrthographicCamera {
+        OrthographicCamera {
+            shutter,
+            ..self
+        }
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn get_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+        Some(Ray {
+            origin: &self.lower_left_corner + s * &self.horizontal + t * &self.vertical,
+            direction: self.direction.clone(),
+            time: self.shutter.sample_time(self.time_start, self.time_end, t, sampler.get_1d()),
+            wavelengths: Wavelengths::Rgb,
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use super::{camera_basis, ApertureShape, Camera, Shutter};
+use crate::matrix4::Matrix4;
+use crate::ray::Ray;
+use crate::spectrum::Wavelengths;
+use crate::sampler::Sampler;
+use crate::vec3::{Point3, Vec3};
+use crate::util::degrees_to_radians;
+
+/// Thin-lens perspective projection, as described in the books.
+pub struct PerspectiveCamera {
+    origin: Point3,
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    w: Vec3,
+    u: Vec3,
+    v: Vec3,
+    lens_radius: f64,
+    focus_dist: f64,
+    aperture_shape: ApertureShape,
+    // Normal of the plane of focus, if it has been tilted away from the image plane.
+    focus_plane_normal: Option<Vec3>,
+    time_start: f64,
+    time_end: f64,
+    shutter: Shutter,
+}
+
+impl PerspectiveCamera {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(lookfrom: Point3, lookat: Point3, vup: Vec3, vfov: f64, aspect_ratio: f64, aperture: f64, focus_dist: f64, time_start: f64, time_end: f64) -> PerspectiveCamera {
+        let theta = degrees_to_radians(vfov);
+        let h = (theta / 2.0).tan();
+        let viewport_height = 2.0 * h;
+        let viewport_width = aspect_ratio * viewport_height;
+
+        let (u, v, w) = camera_basis(&lookfrom, &lookat, &vup);
+
+        let origin = lookfrom;
+        let horizontal = focus_dist * viewport_width * &u;
+        let vertical = focus_dist * viewport_height * &v;
+        PerspectiveCamera {
+            lower_left_corner: &origin
+                - &horizontal / 2.0
+                - &vertical / 2.0
+                - focus_dist * &w,
+            origin,
+            horizontal,
+            vertical,
+            w,
+            u,
+            v,
+            lens_radius: aperture / 2.0,
+            focus_dist,
+            aperture_shape: ApertureShape::Circle,
+            focus_plane_normal: None,
+            time_start,
+            time_end,
+            shutter: Shutter::default(),
+        }
+    }
+
+    pub fn with_shutter(self, shutter: Shutter) -> PerspectiveCamera {
+        PerspectiveCamera {
+            shutter,
+            ..self
+        }
+    }
+
+    pub fn with_aperture_shape(self, aperture_shape: ApertureShape) -> PerspectiveCamera {
+        PerspectiveCamera {
+            aperture_shape,
+            ..self
+        }
+    }
+
+    /// Tilt-shift lens movements. `tilt` and `swing` rotate the plane of focus about the horizontal and vertical
+    /// axes of the image, in degrees. `shift_x` and `shift_y` move the image within the image circle, as fractions
+    /// of the frame width and height, without changing perspective.
+    pub fn with_tilt_shift(self, tilt: f64, swing: f64, shift_x: f64, shift_y: f64) -> PerspectiveCamera {
+        let lower_left_corner = &self.lower_left_corner + shift_x * &self.horizontal + shift_y * &self.vertical;
+        let focus_plane_normal = if tilt == 0.0 && swing == 0.0 {
+            None
+        } else {
+            let rotation = Matrix4::rotation(&self.v, swing) * Matrix4::rotation(&self.u, tilt);
+            Some(rotation.transform_vector(&self.w))
+        };
+        PerspectiveCamera {
+            lower_left_corner,
+            focus_plane_normal,
+            ..self
+        }
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    fn get_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+        let rd = self.lens_radius * self.aperture_shape.sample(sampler);
+        let offset = &self.u * rd.x + &self.v * rd.y;
+        let mut target = &self.lower_left_corner + s * &self.horizontal + t * &self.vertical;
+        if let Some(normal) = &self.focus_plane_normal {
+            // Everything along the ray through the centre of the lens is focused where it meets the tilted plane.
+            let chief_ray = &target - &self.origin;
+            let plane_point = &self.origin - self.focus_dist * &self.w;
+            let distance = (&plane_point - &self.origin).dot(normal) / chief_ray.dot(normal);
+            if distance > 0.0 {
+                target = &self.origin + distance * chief_ray;
+            }
+        }
+        Some(Ray {
+            origin: &self.origin + &offset,
+            direction: target - &self.origin - &offset,
+            time: self.shutter.sample_time(self.time_start, self.time_end, t, sampler.get_1d()),
+            wavelengths: Wavelengths::Rgb,
+        })
+    }
+}
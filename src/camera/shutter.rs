@@ -0,0 +1,122 @@
+/// How far open the shutter is over the course of an exposure.
+#[derive(Clone)]
+pub enum ShutterCurve {
+    /// Fully open for the whole exposure, the same as an ideal global shutter.
+    Box,
+    /// Opens linearly until the middle of the exposure, then closes linearly.
+    Triangle,
+    /// Openness sampled at evenly spaced points from the start to the end of the exposure,
+    /// linearly interpolated in between. The values only need to be relative to each other.
+    Custom(Vec<f64>),
+}
+
+/// Decides the time each camera ray is traced at.
+///
+/// With a global shutter, every scanline sees the whole `[time_start, time_end]` window. With a rolling shutter,
+/// scanlines are read out from the top of the image to the bottom over `readout` (a fraction of the window),
+/// and each one is only exposed for the remaining `1.0 - readout` of it.
+#[derive(Clone)]
+pub struct Shutter {
+    curve: ShutterCurve,
+    readout: f64,
+    // Cumulative distribution of a `Custom` curve, with one more entry than it has values.
+    cdf: Vec<f64>,
+}
+
+impl Shutter {
+    /// Panics if a `Custom` curve has values that aren't finite.
+    pub fn new(curve: ShutterCurve) -> Self {
+        if let ShutterCurve::Custom(values) = &curve {
+            assert!(values.iter().all(|value| value.is_finite()), "Shutter curve values must be finite!");
+        }
+        let cdf = match &curve {
+            ShutterCurve::Custom(values) => Self::build_cdf(values),
+            _ => Vec::new(),
+        };
+        Self {
+            curve,
+            readout: 0.0,
+            cdf,
+        }
+    }
+
+    /// Panics if `readout` is not in `[0.0, 1.0)`.
+    pub fn rolling(curve: ShutterCurve, readout: f64) -> Self {
+        assert!((0.0..1.0).contains(&readout), "Rolling shutter readout must be in [0.0, 1.0)!");
+        Self {
+            readout,
+            ..Self::new(curve)
+        }
+    }
+
+    /// `t` is the vertical position on the image (0.0 at the bottom, 1.0 at the top),
+    /// and `sample` a uniformly distributed number in `[0.0, 1.0)`.
+    pub fn sample_time(&self, time_start: f64, time_end: f64, t: f64, sample: f64) -> f64 {
+        let duration = time_end - time_start;
+        let row_start = time_start + (1.0 - t.clamp(0.0, 1.0)) * self.readout * duration;
+        row_start + (1.0 - self.readout) * duration * self.sample_curve(sample)
+    }
+
+    fn sample_curve(&self, sample: f64) -> f64 {
+        match &self.curve {
+            ShutterCurve::Box => sample,
+            ShutterCurve::Triangle => {
+                if sample < 0.5 {
+                    (sample / 2.0).sqrt()
+                } else {
+                    1.0 - ((1.0 - sample) / 2.0).sqrt()
+                }
+            },
+            ShutterCurve::Custom(values) => {
+                if values.len() < 2 {
+                    return sample;
+                }
+                // Find the segment of the curve the sample falls in, then invert the CDF of the linear ramp inside it.
+                let segment = match self.cdf.binary_search_by(|probe| probe.partial_cmp(&sample).unwrap()) {
+                    Ok(index) => index.min(values.len() - 2),
+                    Err(index) => index.saturating_sub(1).min(values.len() - 2),
+                };
+                let segment_width = 1.0 / (values.len() - 1) as f64;
+                let a = values[segment].max(0.0);
+                let b = values[segment + 1].max(0.0);
+                let segment_mass = self.cdf[segment + 1] - self.cdf[segment];
+                let target = if segment_mass > 0.0 { (sample - self.cdf[segment]) / segment_mass } else { 0.5 };
+                // Openness is a + (b - a) * x over x in [0, 1]; solve the area up to x for the target fraction.
+                let x = if (b - a).abs() < 1e-9 {
+                    target
+                } else {
+                    let area = target * (a + b) / 2.0;
+                    (-a + (a * a + 2.0 * (b - a) * area).max(0.0).sqrt()) / (b - a)
+                };
+                (segment as f64 + x.clamp(0.0, 1.0)) * segment_width
+            },
+        }
+    }
+
+    fn build_cdf(values: &[f64]) -> Vec<f64> {
+        let mut cdf = vec![0.0];
+        for segment in values.windows(2) {
+            let area = (segment[0].max(0.0) + segment[1].max(0.0)) / 2.0;
+            cdf.push(cdf.last().unwrap() + area);
+        }
+        let total = *cdf.last().unwrap();
+        if total > 0.0 {
+            for value in cdf.iter_mut() {
+                *value /= total;
+            }
+        } else {
+            // A curve that never opens is treated as a box.
+            let segments = (cdf.len() - 1).max(1) as f64;
+            for (i, value) in cdf.iter_mut().enumerate() {
+                *value = i as f64 / segments;
+            }
+        }
+        cdf
+    }
+}
+
+impl Default for Shutter {
+    fn default() -> Self {
+        Self::new(ShutterCurve::Box)
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use crate::filter::Filter;
+use crate::image::{ColorSpace, Image};
+use crate::vec3::Color;
+
+#[derive(Clone)]
+struct FilmPixel {
+    weighted_color: Color,
+    weight: f64,
+}
+
+/// Accumulates samples at continuous positions on the image, splatting each one into every pixel
+/// its reconstruction filter reaches.
+pub struct Film {
+    width: usize,
+    height: usize,
+    filter: Arc<dyn Filter>,
+    /// What the colors of the samples mean, which the image keeps.
+    color_space: ColorSpace,
+    data: Vec<FilmPixel>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize, filter: Arc<dyn Filter>) -> Film {
+        Film {
+            width,
+            height,
+            filter,
+            color_space: ColorSpace::default(),
+            data: vec![FilmPixel { weighted_color: Color::new(), weight: 0.0 }; width * height],
+        }
+    }
+
+    pub fn with_color_space(self, color_space: ColorSpace) -> Film {
+        Film { color_space, ..self }
+    }
+
+    /// `x` and `y` are in pixels, with pixel `(i, j)` covering `[i, i + 1) x [j, j + 1)` and `j = 0` at the bottom.
+    pub fn add_sample(&mut self, x: f64, y: f64, color: Color) {
+        let radius = self.filter.radius();
+        // Pixel centres are at half-integer positions.
+        let x0 = (x - 0.5 - radius).ceil().max(0.0) as usize;
+        let x1 = (x - 0.5 + radius).floor().min(self.width as f64 - 1.0);
+        let y0 = (y - 0.5 - radius).ceil().max(0.0) as usize;
+        let y1 = (y - 0.5 + radius).floor().min(self.height as f64 - 1.0);
+        if x1 < 0.0 || y1 < 0.0 {
+            return;
+        }
+        for j in y0..=(y1 as usize) {
+            for i in x0..=(x1 as usize) {
+                let weight = self.filter.evaluate(i as f64 + 0.5 - x, j as f64 + 0.5 - y);
+                if weight != 0.0 {
+                    let pixel = self.data.get_mut((j * self.width) + i).unwrap();
+                    pixel.weighted_color += weight * &color;
+                    pixel.weight += weight;
+                }
+            }
+        }
+    }
+
+    /// The filtered, but not yet gamma corrected, image.
+    pub fn image(&self) -> Image {
+        let mut image = Image::new(self.width, self.height).with_color_space(self.color_space);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = self.data.get((y * self.width) + x).unwrap();
+                if pixel.weight > 0.0 {
+                    image.set_pixel(x, y, &pixel.weighted_color / pixel.weight);
+                }
+            }
+        }
+        image
+    }
+}
@@ -0,0 +1,48 @@
+use std::f64::consts;
+
+use super::Filter;
+
+/// A sinc windowed by a wider sinc. Sharp, but rings around high-contrast edges.
+pub struct LanczosFilter {
+    radius: f64,
+    // Number of sinc lobes that fit in the radius.
+    tau: f64,
+}
+
+impl LanczosFilter {
+    pub fn new(radius: f64, tau: f64) -> Self {
+        Self { radius, tau }
+    }
+
+    fn sinc(x: f64) -> f64 {
+        if x.abs() < 1e-5 {
+            1.0
+        } else {
+            (consts::PI * x).sin() / (consts::PI * x)
+        }
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        if x.abs() > self.radius {
+            return 0.0;
+        }
+        let x = x / self.radius * self.tau;
+        Self::sinc(x) * Self::sinc(x / self.tau)
+    }
+}
+
+impl Default for LanczosFilter {
+    fn default() -> Self {
+        Self::new(2.0, 2.0)
+    }
+}
+
+impl Filter for LanczosFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f64, y: f64) -> f64 {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+}
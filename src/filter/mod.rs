@@ -0,0 +1,19 @@
+mod box_filter;
+pub use box_filter::BoxFilter;
+mod tent_filter;
+pub use tent_filter::TentFilter;
+mod gaussian_filter;
+pub use gaussian_filter::GaussianFilter;
+mod mitchell_filter;
+pub use mitchell_filter::MitchellFilter;
+mod lanczos_filter;
+pub use lanczos_filter::LanczosFilter;
+
+/// A pixel reconstruction filter. Samples are weighted into every pixel whose centre is within `radius`
+/// of them, by the filter's value at the offset from that centre.
+pub trait Filter: Send + Sync {
+    /// Half the width of the filter's support, in pixels, in both x and y.
+    fn radius(&self) -> f64;
+    /// Weight of a sample `x` and `y` pixels away from a pixel centre. May be negative.
+    fn evaluate(&self, x: f64, y: f64) -> f64;
+}
@@ -22,7 +22,8 @@ impl AABB {
             }
             t_min = if t0 > t_min { t0 } else { t_min };
             t_max = if t1 < t_max { t1 } else { t_max };
-            if t_max <= t_min {
+            // Touching counts as a hit, so flat boxes still work.
+            if t_max < t_min {
                 return false;
             }
         }
@@ -44,3 +45,69 @@ impl AABB {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::test_util::ray;
+
+    fn unit_box() -> AABB {
+        AABB { minimum: Point3 { x: 0.0, y: 0.0, z: 0.0 }, maximum: Point3 { x: 1.0, y: 1.0, z: 1.0 } }
+    }
+
+    #[test]
+    fn hits_and_misses() {
+        let aabb = unit_box();
+        assert!(aabb.hit(&ray((0.5, 0.5, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY));
+        assert!(aabb.hit(&ray((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0)), 0.001, f64::INFINITY));
+        assert!(!aabb.hit(&ray((1.5, 0.5, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY));
+        assert!(!aabb.hit(&ray((-1.0, -1.0, -1.0), (1.0, 1.0, -1.0)), 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn misses_behind_the_ray() {
+        assert!(!unit_box().hit(&ray((0.5, 0.5, 5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn hits_from_inside() {
+        assert!(unit_box().hit(&ray((0.5, 0.5, 0.5), (0.3, -0.2, 1.0)), 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn respects_t_range() {
+        let aabb = unit_box();
+        // The ray is inside the box for t in [5, 6].
+        let ray = ray((0.5, 0.5, -5.0), (0.0, 0.0, 1.0));
+        assert!(!aabb.hit(&ray, 0.001, 4.9));
+        assert!(!aabb.hit(&ray, 6.1, f64::INFINITY));
+        assert!(aabb.hit(&ray, 5.2, 5.8));
+    }
+
+    #[test]
+    fn rays_along_a_face_count_as_hits() {
+        // Boxes must be conservative, so a ray sliding along a face (where the slab test divides zero by zero)
+        // must not be culled.
+        let aabb = unit_box();
+        assert!(aabb.hit(&ray((0.0, 0.5, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY));
+        assert!(aabb.hit(&ray((1.0, 0.5, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY));
+        assert!(aabb.hit(&ray((1.0, 1.0, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn flat_boxes_can_be_hit() {
+        let aabb = AABB { minimum: Point3 { x: 0.0, y: 0.0, z: 0.0 }, maximum: Point3 { x: 1.0, y: 1.0, z: 0.0 } };
+        assert!(aabb.hit(&ray((0.5, 0.5, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY));
+        assert!(aabb.hit(&ray((0.5, 0.5, -5.0), (0.1, 0.0, 1.0)), 0.001, f64::INFINITY));
+        assert!(!aabb.hit(&ray((1.5, 0.5, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn surrounding_box_contains_both() {
+        let a = unit_box();
+        let b = AABB { minimum: Point3 { x: -2.0, y: 0.5, z: 0.5 }, maximum: Point3 { x: -1.0, y: 3.0, z: 0.75 } };
+        let surrounding = a.surrounding_box(&b);
+        assert_eq!((surrounding.minimum.x, surrounding.minimum.y, surrounding.minimum.z), (-2.0, 0.0, 0.0));
+        assert_eq!((surrounding.maximum.x, surrounding.maximum.y, surrounding.maximum.z), (1.0, 3.0, 1.0));
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use crate::{hittable::{HitRecord, Hittable, AABB}, ray::Ray, texture::Texture};
+use crate::sampler::{hash_ray, Sampler};
+
+/// Cuts `hittable` out where `opacity`, read with `Texture::alpha`, is zero, for leaves, fences and decals. Rays go
+/// on to whatever is behind, so shadows have the same holes. Partly opaque hits are let through at random.
+pub struct AlphaMask {
+    hittable: Arc<dyn Hittable>,
+    opacity: Arc<dyn Texture>,
+}
+
+impl AlphaMask {
+    pub fn new(hittable: Arc<dyn Hittable>, opacity: Arc<dyn Texture>) -> Self {
+        Self { hittable, opacity }
+    }
+}
+
+impl Hittable for AlphaMask {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
+        let mut t_min = t_min;
+        loop {
+            let hit_record = self.hittable.hit(ray, t_min, t_max, sampler)?;
+            let opacity = self.opacity.alpha(hit_record.u, hit_record.v, &hit_record.p);
+            if opacity >= 1.0 || (opacity > 0.0 && hash_ray(ray, hit_record.t) < opacity) {
+                return Some(hit_record);
+            }
+            // On to the next surface of the same hittable, like the far side of a sphere.
+            t_min = hit_record.t + 1e-9 * hit_record.t.abs().max(1.0);
+        }
+    }
+
+    fn bounding_box(&self, time_start: f64, time_end: f64) -> Option<AABB> {
+        self.hittable.bounding_box(time_start, time_end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::{HittableList, Sphere, XYRect};
+    use rand::Rng;
+    use crate::hittable::test_util::{assert_close, check_hit_properties, material, ray, rng, sampler};
+    use crate::texture::{CheckerTexture, SolidColor};
+    use crate::vec3::{Color, Point3};
+
+    fn opacity(value: f64) -> Arc<dyn Texture> {
+        Arc::new(SolidColor::from_value(value))
+    }
+
+    fn unit_sphere() -> Arc<dyn Hittable> {
+        Arc::new(Sphere { center: Point3::new(), radius: 1.0, material: material() })
+    }
+
+    #[test]
+    fn opaque_changes_nothing_and_transparent_hides_everything() {
+        let ray = ray((0.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        assert_close(AlphaMask::new(unit_sphere(), opacity(1.0)).hit(&ray, 0.001, f64::INFINITY, &mut sampler()).unwrap().t, 4.0);
+        assert!(AlphaMask::new(unit_sphere(), opacity(0.0)).hit(&ray, 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn cut_out_hits_go_on_to_the_next_surface() {
+        // Transparent where the ray first meets the sphere and opaque where it leaves, so it hits the inside.
+        let mask = AlphaMask::new(unit_sphere(), Arc::new(CheckerTexture::from_colors(Color { x: 1.0, y: 1.0, z: 1.0 }, Color::new())));
+        let hit_record = mask.hit(&ray((0.05, 0.05, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert!(hit_record.t > 5.0 && !hit_record.front_face);
+
+        // And past the whole object, to what is behind it.
+        let mut world = HittableList::new();
+        world.add(Arc::new(AlphaMask::new(unit_sphere(), opacity(0.0))));
+        world.add(Arc::new(XYRect { material: material(), x0: -1.0, x1: 1.0, y0: -1.0, y1: 1.0, k: 3.0 }));
+        assert_close(world.hit(&ray((0.0, 0.0, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap().t, 8.0);
+    }
+
+    #[test]
+    fn partial_opacity_lets_that_fraction_through() {
+        let count = 10_000;
+        let mask = AlphaMask::new(Arc::new(XYRect { material: material(), x0: -1.0, x1: 1.0, y0: -1.0, y1: 1.0, k: 0.0 }), opacity(0.3));
+        let mut rng = rng();
+        let mut sampler = sampler();
+        // Which rays get through is decided by the ray, so it takes different ones.
+        let hits = (0..count).filter(|_| {
+            let ray = ray((rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), -1.0), (0.0, 0.0, 1.0));
+            mask.hit(&ray, 0.001, f64::INFINITY, &mut sampler).is_some()
+        }).count() as f64 / count as f64;
+        assert!((hits - 0.3).abs() < 5.0 * (0.3 * 0.7 / count as f64).sqrt(), "Hit {} of the time", hits);
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        let mask = AlphaMask::new(unit_sphere(), Arc::new(CheckerTexture::from_colors(Color::new(), Color { x: 1.0, y: 1.0, z: 1.0 })));
+        assert!(check_hit_properties(&mask) > 100);
+    }
+}
@@ -1,9 +1,9 @@
 use std::{cmp, sync::Arc};
 
-use rand::seq::SliceRandom;
-
 use crate::hittable::{HitRecord, Hittable, AABB, hittable_list::HittableList};
 use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::stats;
 
 pub struct BVHNode {
     left: Arc<dyn Hittable>,
@@ -23,13 +23,13 @@ impl BVHNode {
         Self::from_objects(&hittable_list.objects, 0, hittable_list.objects.len(), time_start, time_end)
     }
 
-    fn from_objects(src_objects: &Vec<Arc<dyn Hittable>>, start: usize, end: usize, time_start: f64, time_end: f64) -> BVHNode {
-        let mut objects = src_objects.clone();
-        let comparator = [
+    fn from_objects(src_objects: &[Arc<dyn Hittable>], start: usize, end: usize, time_start: f64, time_end: f64) -> BVHNode {
+        let mut objects = src_objects.to_vec();
+        let comparator = &[
             |a: &Arc<dyn Hittable>, b: &Arc<dyn Hittable>| Self::box_compare(a.clone(), b.clone(), Axis::X),
             |a: &Arc<dyn Hittable>, b: &Arc<dyn Hittable>| Self::box_compare(a.clone(), b.clone(), Axis::Y),
             |a: &Arc<dyn Hittable>, b: &Arc<dyn Hittable>| Self::box_compare(a.clone(), b.clone(), Axis::Z),
-        ].choose(&mut rand::thread_rng()).unwrap();
+        ][Self::longest_axis(&objects[start..end], time_start, time_end) as usize];
         let object_span = end - start;
 
         let (left, right) = match object_span {
@@ -56,6 +56,23 @@ impl BVHNode {
         }
     }
 
+    // Splitting along the axis the objects are most spread out on, rather than a random one, keeps the tree
+    // (and so the render) the same from run to run.
+    fn longest_axis(objects: &[Arc<dyn Hittable>], time_start: f64, time_end: f64) -> Axis {
+        let aabb = objects.iter()
+            .map(|object| object.bounding_box(time_start, time_end).expect("No bounding box in bvh_node constructor!"))
+            .reduce(|a, b| a.surrounding_box(&b))
+            .unwrap();
+        let extent = &aabb.maximum - &aabb.minimum;
+        if extent.x > extent.y && extent.x > extent.z {
+            Axis::X
+        } else if extent.y > extent.z {
+            Axis::Y
+        } else {
+            Axis::Z
+        }
+    }
+
     fn box_compare (a: Arc<dyn Hittable>, b: Arc<dyn Hittable>, axis: Axis) -> cmp::Ordering {
         let box_a = a.bounding_box(0.0, 0.0).expect("No bounding box in bvh_node constructor!");
         let box_b = b.bounding_box(0.0, 0.0).expect("No bounding box in bvh_node constructor!");
@@ -66,18 +83,19 @@ impl BVHNode {
 }
 
 impl Hittable for BVHNode {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
+        stats::count_bvh_node_visit();
         if !self.aabb.hit(ray, t_min, t_max) {
             return None
         }
-        let hit_left = self.left.hit(ray, t_min, t_max);
+        let hit_left = self.left.hit(ray, t_min, t_max, sampler);
         let hit_right_threshold = if let Some(hit_record_left) = &hit_left {
             hit_record_left.t
         } else {
             t_max
         };
-        let hit_right = self.right.hit(ray, t_min, hit_right_threshold);
-        if let Some(_) = &hit_right {
+        let hit_right = self.right.hit(ray, t_min, hit_right_threshold, sampler);
+        if hit_right.is_some() {
             hit_right
         } else {
             hit_left
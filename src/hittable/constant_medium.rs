@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use crate::{hittable::{HitRecord, Hittable, AABB}, material::{Isotropic, Material}, ray::Ray, texture::Texture, vec3::Vec3};
+use crate::sampler::{hash_ray, Sampler};
 
 pub struct ConstantMedium {
     boundary: Arc<dyn Hittable>,
@@ -20,9 +21,9 @@ impl ConstantMedium {
 
 impl Hittable for ConstantMedium {
     // TODO: this only support convex shapes.
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let mut record_1 = self.boundary.hit(ray, -f64::INFINITY, f64::INFINITY)?;
-        let mut record_2 = self.boundary.hit(ray, record_1.t + 0.0001, f64::INFINITY)?;
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
+        let mut record_1 = self.boundary.hit(ray, -f64::INFINITY, f64::INFINITY, sampler)?;
+        let mut record_2 = self.boundary.hit(ray, record_1.t + 0.0001, f64::INFINITY, sampler)?;
 
         if record_1.t < t_min {
             record_1.t = t_min;
@@ -41,7 +42,7 @@ impl Hittable for ConstantMedium {
 
         let ray_length = ray.direction.length();
         let distance_inside_boundary = (record_2.t - record_1.t) * ray_length;
-        let hit_distance = self.neg_inv_density * rand::random::<f64>().ln();
+        let hit_distance = self.neg_inv_density * (1.0 - hash_ray(ray, record_1.t)).ln();
 
         if hit_distance > distance_inside_boundary {
             return None;
@@ -53,6 +54,8 @@ impl Hittable for ConstantMedium {
             t,
             material: Some(self.phase_function.clone()),
             normal: Vec3 { x: 1.0, y: 0.0, z: 0.0 }, // arbitrary
+            tangent: Vec3 { x: 0.0, y: 1.0, z: 0.0 }, // arbitrary
+            bitangent: Vec3 { x: 0.0, y: 0.0, z: 1.0 }, // arbitrary
             front_face: true, // arbitrary
             u: 0.0, // arbitrary
             v: 0.0, // arbitrary
@@ -63,3 +66,96 @@ impl Hittable for ConstantMedium {
         self.boundary.bounding_box(time_start, time_end)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use crate::hittable::Sphere;
+    use crate::hittable::test_util::{box_contains, material, ray, rng, sampler, EPSILON};
+    use crate::spectrum::Wavelengths;
+    use crate::texture::SolidColor;
+    use crate::vec3::{Color, Point3};
+
+    fn medium(density: f64) -> ConstantMedium {
+        let boundary = Arc::new(Sphere { center: Point3::new(), radius: 1.0, material: material() });
+        ConstantMedium::new(boundary, density, Arc::new(SolidColor::from_color(Color { x: 1.0, y: 1.0, z: 1.0 })))
+    }
+
+    #[test]
+    fn dense_medium_scatters_at_the_boundary() {
+        let hit_record = medium(1e9).hit(&ray((0.0, 0.0, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert!((hit_record.t - 4.0).abs() < 1e-6);
+        assert!(hit_record.material.is_some());
+    }
+
+    #[test]
+    fn thin_medium_lets_rays_through() {
+        assert!(medium(1e-12).hit(&ray((0.0, 0.0, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn misses_when_the_boundary_is_missed() {
+        assert!(medium(1e9).hit(&ray((0.0, 2.0, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+        assert!(medium(1e9).hit(&ray((0.0, 0.0, 5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn scatters_from_inside() {
+        let hit_record = medium(1e9).hit(&ray((0.0, 0.0, 0.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert!(hit_record.t >= 0.001 && hit_record.t < 0.002);
+    }
+
+    #[test]
+    fn respects_t_range() {
+        let medium = medium(1e9);
+        let ray = ray((0.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        assert!(medium.hit(&ray, 0.001, 3.9, &mut sampler()).is_none());
+        assert!(medium.hit(&ray, 6.1, f64::INFINITY, &mut sampler()).is_none());
+        let hit_record = medium.hit(&ray, 5.0, f64::INFINITY, &mut sampler()).unwrap();
+        assert!((hit_record.t - 5.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn free_flight_distance_is_exponential() {
+        // With density 1, the mean free path is 1, so a ray through the unit sphere's diameter scatters with
+        // probability 1 - e^-2.
+        let medium = medium(1.0);
+        let mut rng = rng();
+        let mut sampler = sampler();
+        let cases = 20000;
+        let mut scattered = 0;
+        for _ in 0..cases {
+            // Where a ray scatters is decided by the ray, so it takes different ones, all close to the diameter.
+            let origin = (1e-4 * rng.gen::<f64>(), 1e-4 * rng.gen::<f64>(), -5.0);
+            if medium.hit(&ray(origin, (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler).is_some() {
+                scattered += 1;
+            }
+        }
+        let expected = 1.0 - (-2.0f64).exp();
+        assert!((scattered as f64 / cases as f64 - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        let medium = medium(0.5);
+        let aabb = medium.bounding_box(0.0, 1.0).unwrap();
+        let mut rng = rng();
+        let mut sampler = sampler();
+        let mut hits = 0;
+        for index in 0..2000 {
+            sampler.start_pixel_sample(0, 0, index);
+            let origin = 3.0 * Vec3::random_in_range(&mut rng, -1.0, 1.0);
+            let target = Vec3::random_in_range(&mut rng, -1.0, 1.0);
+            let ray = Ray { direction: (&target - &origin) * rng.gen_range(0.1..10.0), origin, time: 0.0, wavelengths: Wavelengths::Rgb };
+            if let Some(hit_record) = medium.hit(&ray, 0.001, f64::INFINITY, &mut sampler) {
+                hits += 1;
+                assert!(hit_record.t >= 0.001);
+                assert!(box_contains(&aabb, &hit_record.p, EPSILON));
+                assert!(hit_record.p.length() <= 1.0 + 1e-6);
+                assert!((hit_record.normal.length() - 1.0).abs() < 1e-9);
+            }
+        }
+        assert!(hits > 100);
+    }
+}
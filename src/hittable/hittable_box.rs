@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use crate::{hittable::{HitRecord, Hittable, AABB, hittable_list::HittableList, xy_rect::XYRect, xz_rect::XZRect, yz_rect::YZRect}, material::Material, ray::Ray, vec3::Point3};
+use crate::sampler::Sampler;
 
 pub struct HittableBox {
     min: Point3,
@@ -30,8 +31,8 @@ impl HittableBox {
 }
 
 impl Hittable for HittableBox {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        self.sides.hit(ray, t_min, t_max)
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
+        self.sides.hit(ray, t_min, t_max, sampler)
     }
 
     fn bounding_box(&self, _: f64, _: f64) -> Option<AABB> {
@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use crate::hittable::{HitRecord, Hittable, AABB};
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 
+#[derive(Default)]
 pub struct HittableList {
     pub objects: Vec<Arc<dyn Hittable>>,
 }
@@ -24,12 +26,12 @@ impl HittableList {
 }
 
 impl Hittable for HittableList {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
         let mut record = None;
         let mut closest_so_far = t_max;
 
         for object in &self.objects {
-            let temp_rec = object.hit(ray, t_min, closest_so_far);
+            let temp_rec = object.hit(ray, t_min, closest_so_far, sampler);
             if let Some(hit_record) = &temp_rec {
                 closest_so_far = hit_record.t;
                 record = temp_rec;
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use crate::{hittable::{HitRecord, Hittable, AABB}, matrix4::Matrix4, quaternion::Quaternion, ray::Ray, vec3::Vec3};
+use super::Transform;
+use crate::sampler::Sampler;
+
+// Number of steps each keyframe segment is split into when bounding the motion.
+const BOUNDING_STEPS: usize = 16;
+
+#[derive(Clone)]
+pub struct Keyframe {
+    pub time: f64,
+    pub translation: Vec3,
+    pub rotation: Quaternion,
+    pub scale: Vec3,
+}
+
+impl Keyframe {
+    pub fn new(time: f64, translation: Vec3, rotation: Quaternion, scale: Vec3) -> Self {
+        Self { time, translation, rotation, scale }
+    }
+
+    fn matrix(&self) -> Matrix4 {
+        Matrix4::translation(&self.translation) * self.rotation.to_matrix() * Matrix4::scale(&self.scale)
+    }
+
+    fn inverse_matrix(&self) -> Matrix4 {
+        let inverse_scale = Vec3 { x: 1.0 / self.scale.x, y: 1.0 / self.scale.y, z: 1.0 / self.scale.z };
+        Matrix4::scale(&inverse_scale) * self.rotation.conjugate().to_matrix() * Matrix4::translation(&-&self.translation)
+    }
+}
+
+/// A transform that changes over time, for motion blur of rotating and scaling objects as well as moving ones.
+/// Translation and scale are interpolated linearly between keyframes and rotation is slerped.
+/// Outside the keyframed range the first or last keyframe is held.
+pub struct AnimatedTransform {
+    hittable: Arc<dyn Hittable>,
+    keyframes: Vec<Keyframe>,
+}
+
+impl AnimatedTransform {
+    /// Panics if `keyframes` is empty or any keyframe has a zero scale component.
+    pub fn new(hittable: Arc<dyn Hittable>, keyframes: Vec<Keyframe>) -> Self {
+        assert!(!keyframes.is_empty(), "AnimatedTransform needs at least one keyframe!");
+        assert!(
+            keyframes.iter().all(|keyframe| keyframe.scale.x != 0.0 && keyframe.scale.y != 0.0 && keyframe.scale.z != 0.0),
+            "AnimatedTransform keyframes must have non-zero scale!"
+        );
+        let mut keyframes = keyframes;
+        keyframes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        Self {
+            hittable,
+            keyframes,
+        }
+    }
+
+    pub fn keyframe_at(&self, time: f64) -> Keyframe {
+        let first = self.keyframes.first().unwrap();
+        let last = self.keyframes.last().unwrap();
+        if time <= first.time {
+            return first.clone();
+        }
+        if time >= last.time {
+            return last.clone();
+        }
+        let next_index = self.keyframes.iter().position(|keyframe| keyframe.time > time).unwrap();
+        let previous = &self.keyframes[next_index - 1];
+        let next = &self.keyframes[next_index];
+        let t = (time - previous.time) / (next.time - previous.time);
+        Keyframe {
+            time,
+            translation: &previous.translation + t * (&next.translation - &previous.translation),
+            rotation: previous.rotation.slerp(&next.rotation, t),
+            scale: &previous.scale + t * (&next.scale - &previous.scale),
+        }
+    }
+
+    fn corners(aabb: &AABB) -> Vec<Vec3> {
+        let mut corners = Vec::with_capacity(8);
+        for i in 0..2 {
+            for j in 0..2 {
+                for k in 0..2 {
+                    corners.push(Vec3 {
+                        x: if i == 0 { aabb.minimum.x } else { aabb.maximum.x },
+                        y: if j == 0 { aabb.minimum.y } else { aabb.maximum.y },
+                        z: if k == 0 { aabb.minimum.z } else { aabb.maximum.z },
+                    });
+                }
+            }
+        }
+        corners
+    }
+}
+
+impl Hittable for AnimatedTransform {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
+        let keyframe = self.keyframe_at(ray.time);
+        Transform::hit_transformed(self.hittable.as_ref(), &keyframe.matrix(), &keyframe.inverse_matrix(), ray, t_min, t_max, sampler)
+    }
+
+    // The box is the union of the transformed box at closely spaced times, grown by the furthest any corner
+    // moves between two neighbouring samples. A corner travels along a line or a short circular arc between
+    // samples, so it never strays further than that from either end.
+    fn bounding_box(&self, time_start: f64, time_end: f64) -> Option<AABB> {
+        let object_box = self.hittable.bounding_box(time_start, time_end)?;
+        let corners = Self::corners(&object_box);
+
+        let mut times = vec![time_start];
+        times.extend(self.keyframes.iter().map(|keyframe| keyframe.time).filter(|&time| time > time_start && time < time_end));
+        times.push(time_end);
+
+        let mut output_box: Option<AABB> = None;
+        let mut padding: f64 = 0.0;
+        let mut previous_corners: Option<Vec<Vec3>> = None;
+        for segment in times.windows(2) {
+            for step in 0..=BOUNDING_STEPS {
+                let time = segment[0] + (segment[1] - segment[0]) * step as f64 / BOUNDING_STEPS as f64;
+                let matrix = self.keyframe_at(time).matrix();
+                let moved_corners: Vec<Vec3> = corners.iter().map(|corner| matrix.transform_point(corner)).collect();
+                if let Some(previous_corners) = &previous_corners {
+                    for (previous, current) in previous_corners.iter().zip(moved_corners.iter()) {
+                        padding = padding.max((current - previous).length());
+                    }
+                }
+                let moved_box = Transform::transform_box(&matrix, &object_box);
+                output_box = match output_box {
+                    Some(aabb) => Some(aabb.surrounding_box(&moved_box)),
+                    None => Some(moved_box),
+                };
+                previous_corners = Some(moved_corners);
+            }
+        }
+        let output_box = output_box?;
+        let padding = Vec3 { x: padding, y: padding, z: padding };
+        Some(AABB {
+            minimum: &output_box.minimum - &padding,
+            maximum: &output_box.maximum + &padding,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::HittableBox;
+    use crate::hittable::test_util::{assert_close, assert_vec_close, check_hit_properties, material, ray, sampler};
+    use crate::vec3::Point3;
+
+    fn spinning_box() -> AnimatedTransform {
+        let cube = Arc::new(HittableBox::new(Point3 { x: -1.0, y: -0.5, z: -0.25 }, Point3 { x: 1.0, y: 0.5, z: 0.25 }, material()));
+        let axis = Vec3 { x: 0.0, y: 1.0, z: 0.0 };
+        AnimatedTransform::new(cube, vec![
+            Keyframe::new(0.0, Vec3::new(), Quaternion::identity(), Vec3 { x: 1.0, y: 1.0, z: 1.0 }),
+            Keyframe::new(0.5, Vec3 { x: 1.0, y: 0.0, z: 0.0 }, Quaternion::from_axis_angle(&axis, 90.0), Vec3 { x: 1.0, y: 2.0, z: 1.0 }),
+            Keyframe::new(1.0, Vec3 { x: 2.0, y: 1.0, z: 0.0 }, Quaternion::from_axis_angle(&axis, 180.0), Vec3 { x: 0.5, y: 1.0, z: 1.0 }),
+        ])
+    }
+
+    #[test]
+    fn holds_the_end_keyframes() {
+        let animated = spinning_box();
+        assert_vec_close(&animated.keyframe_at(-1.0).translation, &Vec3::new());
+        assert_vec_close(&animated.keyframe_at(2.0).translation, &Vec3 { x: 2.0, y: 1.0, z: 0.0 });
+        assert_vec_close(&animated.keyframe_at(0.25).translation, &Vec3 { x: 0.5, y: 0.0, z: 0.0 });
+    }
+
+    #[test]
+    fn hits_the_pose_at_the_ray_time() {
+        let animated = spinning_box();
+        // At t = 0.5 the box is turned a quarter, so its long side runs along z, and it is shifted to x = 1.
+        let mut ray = ray((1.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        ray.time = 0.5;
+        let hit_record = animated.hit(&ray, 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 4.0);
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 0.0, y: 0.0, z: -1.0 });
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        assert!(check_hit_properties(&spinning_box()) > 100);
+    }
+}
@@ -1,10 +1,6 @@
 mod moving;
 pub use moving::Moving;
-mod rotate_y;
-pub use rotate_y::RotateY;
-mod rotate_x;
-pub use rotate_x::RotateX;
-mod rotate_z;
-pub use rotate_z::RotateZ;
-mod translate;
-pub use translate::Translate;
+mod transform;
+pub use transform::Transform;
+mod animated_transform;
+pub use animated_transform::{AnimatedTransform, Keyframe};
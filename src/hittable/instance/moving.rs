@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use crate::{ray::Ray, vec3::Vec3};
 use crate::hittable::{HitRecord, Hittable, AABB};
+use crate::sampler::Sampler;
 
 pub struct Moving {
     pub hittable: Arc<dyn Hittable>,
@@ -18,12 +19,11 @@ impl Moving {
 }
 
 impl Hittable for Moving {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let moved_ray = Ray { origin: &ray.origin - &self.offset_at(ray.time), direction: ray.direction.clone(), time: ray.time };
-        let mut hit_record = self.hittable.hit(&moved_ray, t_min, t_max)?;
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
+        let moved_ray = Ray { origin: &ray.origin - &self.offset_at(ray.time), direction: ray.direction.clone(), time: ray.time, wavelengths: ray.wavelengths };
+        let mut hit_record = self.hittable.hit(&moved_ray, t_min, t_max, sampler)?;
+        // The ray direction is unchanged, so the normal and which face was hit carry over as they are.
         hit_record.p += self.offset_at(ray.time).clone();
-        let normal = hit_record.normal.clone();
-        hit_record.set_face_normal(&moved_ray, &normal);
         Some(hit_record)
     }
 
@@ -38,3 +38,59 @@ impl Hittable for Moving {
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::Sphere;
+    use crate::hittable::test_util::{assert_close, assert_vec_close, check_hit_properties, material, ray, sampler};
+    use crate::vec3::Point3;
+
+    fn moving_sphere() -> Moving {
+        Moving {
+            hittable: Arc::new(Sphere { center: Point3::new(), radius: 1.0, material: material() }),
+            offset_start: Vec3::new(),
+            offset_end: Vec3 { x: 4.0, y: 0.0, z: 0.0 },
+            time_start: 0.0,
+            time_end: 1.0,
+        }
+    }
+
+    #[test]
+    fn follows_the_motion() {
+        let moving = moving_sphere();
+        for (time, x) in [(0.0, 0.0), (0.5, 2.0), (1.0, 4.0)] {
+            let mut ray = ray((x, 0.0, -5.0), (0.0, 0.0, 1.0));
+            ray.time = time;
+            let hit_record = moving.hit(&ray, 0.001, f64::INFINITY, &mut sampler()).unwrap();
+            assert_close(hit_record.t, 4.0);
+            assert_vec_close(&hit_record.p, &Point3 { x, y: 0.0, z: -1.0 });
+            assert_vec_close(&hit_record.normal, &Vec3 { x: 0.0, y: 0.0, z: -1.0 });
+        }
+        // Where the sphere started, it is gone by the end of the shutter.
+        let mut ray = ray((0.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        ray.time = 1.0;
+        assert!(moving.hit(&ray, 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn hits_from_inside() {
+        let mut ray = ray((2.0, 0.0, 0.0), (0.0, 0.0, 1.0));
+        ray.time = 0.5;
+        let hit_record = moving_sphere().hit(&ray, 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert!(!hit_record.front_face);
+        assert_close(hit_record.t, 1.0);
+    }
+
+    #[test]
+    fn bounding_box_covers_the_whole_path() {
+        let aabb = moving_sphere().bounding_box(0.0, 1.0).unwrap();
+        assert_vec_close(&aabb.minimum, &Vec3 { x: -1.0, y: -1.0, z: -1.0 });
+        assert_vec_close(&aabb.maximum, &Vec3 { x: 5.0, y: 1.0, z: 1.0 });
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        assert!(check_hit_properties(&moving_sphere()) > 100);
+    }
+}
@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use crate::{hittable::{HitRecord, Hittable, AABB}, matrix4::Matrix4, ray::Ray, vec3::Point3};
+use crate::sampler::Sampler;
+
+/// Places a hittable in the world with an arbitrary affine transform, replacing chains of
+/// rotation and translation wrappers with a single matrix.
+pub struct Transform {
+    hittable: Arc<dyn Hittable>,
+    matrix: Matrix4,
+    inverse: Matrix4,
+}
+
+impl Transform {
+    /// Panics if `matrix` is not invertible, e.g. a scale of zero along some axis.
+    pub fn new(hittable: Arc<dyn Hittable>, matrix: Matrix4) -> Self {
+        let inverse = matrix.inverse().expect("Transform matrix is not invertible!");
+        Self {
+            hittable,
+            matrix,
+            inverse,
+        }
+    }
+
+    pub fn matrix(&self) -> &Matrix4 {
+        &self.matrix
+    }
+
+    pub(crate) fn transform_box(matrix: &Matrix4, aabb: &AABB) -> AABB {
+        let mut min = Point3 { x: f64::INFINITY, y: f64::INFINITY, z: f64::INFINITY };
+        let mut max = Point3 { x: -f64::INFINITY, y: -f64::INFINITY, z: -f64::INFINITY };
+        for i in 0..2 {
+            for j in 0..2 {
+                for k in 0..2 {
+                    let corner = Point3 {
+                        x: if i == 0 { aabb.minimum.x } else { aabb.maximum.x },
+                        y: if j == 0 { aabb.minimum.y } else { aabb.maximum.y },
+                        z: if k == 0 { aabb.minimum.z } else { aabb.maximum.z },
+                    };
+                    let tester = matrix.transform_point(&corner);
+                    for c in 0..3 {
+                        *min.get_mut(c).unwrap() = min.get(c).unwrap().min(*tester.get(c).unwrap());
+                        *max.get_mut(c).unwrap() = max.get(c).unwrap().max(*tester.get(c).unwrap());
+                    }
+                }
+            }
+        }
+        AABB { minimum: min, maximum: max }
+    }
+
+    /// Runs `hittable.hit` in object space and maps the result back to world space.
+    /// The ray direction is transformed but not renormalized, so `t` means the same thing in both spaces.
+    pub(crate) fn hit_transformed(hittable: &dyn Hittable, matrix: &Matrix4, inverse: &Matrix4, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
+        let object_ray = Ray {
+            origin: inverse.transform_point(&ray.origin),
+            direction: inverse.transform_vector(&ray.direction),
+            time: ray.time,
+            wavelengths: ray.wavelengths,
+        };
+        let mut hit_record = hittable.hit(&object_ray, t_min, t_max, sampler)?;
+        // Undo the face flip done in object space before transforming the normal.
+        let object_normal = if hit_record.front_face { hit_record.normal.clone() } else { -&hit_record.normal };
+        let normal = inverse.transform_normal(&object_normal).unit_vector();
+        hit_record.p = matrix.transform_point(&hit_record.p);
+        hit_record.tangent = matrix.transform_vector(&hit_record.tangent);
+        hit_record.bitangent = matrix.transform_vector(&hit_record.bitangent);
+        hit_record.set_face_normal(ray, &normal);
+        Some(hit_record)
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
+        Self::hit_transformed(self.hittable.as_ref(), &self.matrix, &self.inverse, ray, t_min, t_max, sampler)
+    }
+
+    fn bounding_box(&self, time_start: f64, time_end: f64) -> Option<AABB> {
+        let aabb = self.hittable.bounding_box(time_start, time_end)?;
+        Some(Self::transform_box(&self.matrix, &aabb))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::{HittableBox, Sphere, XYRect};
+    use crate::hittable::test_util::{assert_close, assert_vec_close, check_hit_properties, material, ray, sampler};
+    use crate::vec3::Vec3;
+
+    fn unit_sphere() -> Arc<dyn Hittable> {
+        Arc::new(Sphere { center: Point3::new(), radius: 1.0, material: material() })
+    }
+
+    #[test]
+    fn translation_moves_the_hit() {
+        let transform = Transform::new(unit_sphere(), Matrix4::translation(&Vec3 { x: 5.0, y: 0.0, z: 0.0 }));
+        let hit_record = transform.hit(&ray((0.0, 0.0, 0.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 4.0);
+        assert_vec_close(&hit_record.p, &Point3 { x: 4.0, y: 0.0, z: 0.0 });
+        assert_vec_close(&hit_record.normal, &Vec3 { x: -1.0, y: 0.0, z: 0.0 });
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn rotation_turns_the_normal() {
+        // The XY rect faces +z; rotated a quarter turn about y it faces +x.
+        let rect = Arc::new(XYRect { material: material(), x0: -1.0, x1: 1.0, y0: -1.0, y1: 1.0, k: 0.0 });
+        let transform = Transform::new(rect, Matrix4::rotation_y(90.0));
+        let hit_record = transform.hit(&ray((5.0, 0.5, 0.5), (-1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 5.0);
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 1.0, y: 0.0, z: 0.0 });
+        assert!(hit_record.front_face);
+        assert!(transform.hit(&ray((0.5, 0.5, 5.0), (0.0, 0.0, -1.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn non_uniform_scale_keeps_t_and_unit_normals() {
+        let transform = Transform::new(unit_sphere(), Matrix4::scale(&Vec3 { x: 2.0, y: 1.0, z: 1.0 }));
+        let hit_record = transform.hit(&ray((-5.0, 0.0, 0.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 3.0);
+        assert_vec_close(&hit_record.normal, &Vec3 { x: -1.0, y: 0.0, z: 0.0 });
+        // Off axis the normal is no longer the position; it must still be unit length.
+        let hit_record = transform.hit(&ray((1.0, 5.0, 0.0), (0.0, -1.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.normal.length(), 1.0);
+        assert!(hit_record.normal.x > 0.0 && hit_record.normal.y > 0.0);
+    }
+
+    #[test]
+    fn hits_from_inside() {
+        let transform = Transform::new(unit_sphere(), Matrix4::translation(&Vec3 { x: 5.0, y: 0.0, z: 0.0 }));
+        let hit_record = transform.hit(&ray((5.0, 0.0, 0.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert!(!hit_record.front_face);
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 0.0, y: 0.0, z: -1.0 });
+    }
+
+    #[test]
+    fn respects_t_range() {
+        let transform = Transform::new(unit_sphere(), Matrix4::uniform_scale(2.0));
+        let ray = ray((-5.0, 0.0, 0.0), (1.0, 0.0, 0.0));
+        assert!(transform.hit(&ray, 0.001, 2.9, &mut sampler()).is_none());
+        assert_close(transform.hit(&ray, 3.1, f64::INFINITY, &mut sampler()).unwrap().t, 7.0);
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        let cube = Arc::new(HittableBox::new(Point3::new(), Point3 { x: 1.0, y: 2.0, z: 3.0 }, material()));
+        let matrix = Matrix4::translation(&Vec3 { x: 3.0, y: -1.0, z: 2.0 }) * Matrix4::from_euler(30.0, 45.0, -10.0) * Matrix4::scale(&Vec3 { x: 1.5, y: 0.5, z: 2.0 });
+        assert!(check_hit_properties(&Transform::new(cube, matrix)) > 100);
+        let sheared = Transform::new(unit_sphere(), Matrix4::shear(0.5, 0.0, 0.0, 0.3, 0.0, 0.0));
+        assert!(check_hit_properties(&sheared) > 100);
+    }
+}
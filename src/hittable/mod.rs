@@ -19,19 +19,28 @@ mod triangle;
 pub use triangle::Triangle;
 mod model;
 pub use model::Model;
+mod alpha_mask;
+pub use alpha_mask::AlphaMask;
 mod aabb;
+pub use aabb::AABB;
+#[cfg(test)]
+mod test_util;
 
 use std::sync::Arc;
 
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
 use crate::material::Material;
-use aabb::AABB;
+use crate::sampler::Sampler;
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct HitRecord {
     pub p: Point3,
     pub normal: Vec3,
+    /// How `p` moves with `u` and `v`, for orienting things on the surface like bumps, normal maps and anisotropic
+    /// highlights. Not normalized, nor perpendicular to each other, nor facing any particular way like `normal`.
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
     pub material: Option<Arc<dyn Material>>,
     pub t: f64,
     pub u: f64,
@@ -52,6 +61,8 @@ impl HitRecord {
                 y: 0.0,
                 z: 0.0,
             },
+            tangent: Vec3::new(),
+            bitangent: Vec3::new(),
             material: None,
             t: 0.0,
             u: 0.0,
@@ -61,7 +72,8 @@ impl HitRecord {
     }
 
     pub fn set_face_normal(&mut self, ray: &Ray, outward_normal: &Vec3) {
-        self.front_face = ray.direction.dot(&outward_normal) < 0.0;
+        // Grazing rays, perpendicular to the normal, count as hitting the front.
+        self.front_face = ray.direction.dot(outward_normal) <= 0.0;
         self.normal = if self.front_face {
             outward_normal.clone()
         } else {
@@ -70,7 +82,41 @@ impl HitRecord {
     }
 }
 
+/// Anything rays can hit. Implement it for new kinds of geometry:
+///
+/// ```
+/// use ray_tracing_in_one_weekend::hittable::{HitRecord, Hittable, AABB};
+/// use ray_tracing_in_one_weekend::ray::Ray;
+/// use ray_tracing_in_one_weekend::sampler::{Sampler, SamplerKind};
+/// use ray_tracing_in_one_weekend::vec3::{Point3, Vec3};
+///
+/// // The plane y = 0, facing up.
+/// struct Ground;
+///
+/// impl Hittable for Ground {
+///     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _: &mut dyn Sampler) -> Option<HitRecord> {
+///         let t = -ray.origin.y / ray.direction.y;
+///         if !(t_min..t_max).contains(&t) {
+///             return None;
+///         }
+///         let mut hit_record = HitRecord { t, p: ray.at(t), ..HitRecord::new() };
+///         hit_record.tangent = Vec3 { x: 1.0, y: 0.0, z: 0.0 };
+///         hit_record.bitangent = Vec3 { x: 0.0, y: 0.0, z: -1.0 };
+///         hit_record.set_face_normal(ray, &Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+///         Some(hit_record)
+///     }
+///
+///     // Infinite, so it can't go in a BVH.
+///     fn bounding_box(&self, _: f64, _: f64) -> Option<AABB> {
+///         None
+///     }
+/// }
+///
+/// let mut sampler = SamplerKind::Independent.build(1, 0);
+/// let ray = Ray { origin: Point3 { x: 0.0, y: 2.0, z: 0.0 }, direction: Vec3 { x: 1.0, y: -1.0, z: 0.0 }, ..Default::default() };
+/// assert_eq!(Ground.hit(&ray, 0.001, f64::INFINITY, sampler.as_mut()).unwrap().t, 2.0);
+/// ```
 pub trait Hittable: Send + Sync {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord>;
     fn bounding_box(&self, time_start: f64, time_end: f64) -> Option<AABB>;
 }
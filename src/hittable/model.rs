@@ -1,7 +1,9 @@
 use std::sync::Arc;
 use std::vec::Vec;
 
-use crate::{hittable::{HitRecord, Hittable, AABB, HittableList, Triangle}, material::Material, ray::Ray, vec3::{Point3, Vec3}};
+use crate::{hittable::{HitRecord, Hittable, AABB, HittableList, Triangle}, material::Material, ray::Ray, vec3::Vec3};
+use crate::sampler::Sampler;
+use crate::log;
 
 pub struct Model {
     faces: HittableList,
@@ -18,7 +20,9 @@ impl Model {
         let mut vertex_normals: Vec<Vec3> = Vec::new();
         // no free-form objects, so no parameter-space vertices!
         let mut faces: Vec<Arc<dyn Hittable>> = Vec::new();
-        for entry in obj_data.lines() {
+        for (line_index, entry) in obj_data.lines().enumerate() {
+            // Where a warning about this entry points to, for finding it in the file.
+            let line = || ("line", (line_index + 1).to_string());
             if entry.starts_with("#") {
                 // comment
                 continue;
@@ -32,13 +36,13 @@ impl Model {
                 "v" | "vn" => {
                     let x = match entry_iter.next() {
                         None => {
-                            eprintln!("Malformed {} entry in OBJ: Missing x!", operator);
+                            log::warn("obj", "Malformed entry: missing x", &[line(), ("operator", operator.to_string())]);
                             continue;
                         },
                         Some(val) => {
                             match val.parse::<f64>() {
                                 Err(_) => {
-                                    eprintln!("Malformed {} entry in OBJ: Malformed f64 x!", operator);
+                                    log::warn("obj", "Malformed entry: malformed number for x", &[line(), ("operator", operator.to_string())]);
                                     continue;
                                 },
                                 Ok(val) => val,
@@ -47,13 +51,13 @@ impl Model {
                     };
                     let y = match entry_iter.next() {
                         None => {
-                            eprintln!("Malformed {} entry in OBJ: Missing y!", operator);
+                            log::warn("obj", "Malformed entry: missing y", &[line(), ("operator", operator.to_string())]);
                             continue;
                         },
                         Some(val) => {
                             match val.parse::<f64>() {
                                 Err(_) => {
-                                    eprintln!("Malformed {} entry in OBJ: Malformed f64 y!", operator);
+                                    log::warn("obj", "Malformed entry: malformed number for y", &[line(), ("operator", operator.to_string())]);
                                     continue;
                                 },
                                 Ok(val) => val,
@@ -62,13 +66,13 @@ impl Model {
                     };
                     let z = match entry_iter.next() {
                         None => {
-                            eprintln!("Malformed {} entry in OBJ: Missing z!", operator);
+                            log::warn("obj", "Malformed entry: missing z", &[line(), ("operator", operator.to_string())]);
                             continue;
                         },
                         Some(val) => {
                             match val.parse::<f64>() {
                                 Err(_) => {
-                                    eprintln!("Malformed {} entry in OBJ: Malformed f64 z!", operator);
+                                    log::warn("obj", "Malformed entry: malformed number for z", &[line(), ("operator", operator.to_string())]);
                                     continue;
                                 },
                                 Ok(val) => val,
@@ -85,13 +89,13 @@ impl Model {
                 "vt" => {
                     let u = match entry_iter.next() {
                         None => {
-                            eprintln!("Malformed vt entry in OBJ: Missing u!");
+                            log::warn("obj", "Malformed entry: missing u", &[line(), ("operator", operator.to_string())]);
                             continue;
                         },
                         Some(val) => {
                             match val.parse::<f64>() {
                                 Err(_) => {
-                                    eprintln!("Malformed vt entry in OBJ: Malformed f64 u!");
+                                    log::warn("obj", "Malformed entry: malformed number for u", &[line(), ("operator", operator.to_string())]);
                                     continue;
                                 },
                                 Ok(val) => val,
@@ -100,13 +104,13 @@ impl Model {
                     };
                     let v = match entry_iter.next() {
                         None => {
-                            eprintln!("Malformed vt entry in OBJ: Missing v!");
+                            log::warn("obj", "Malformed entry: missing v", &[line(), ("operator", operator.to_string())]);
                             continue;
                         },
                         Some(val) => {
                             match val.parse::<f64>() {
                                 Err(_) => {
-                                    eprintln!("Malformed v entry in OBJ: Malformed f64 v!");
+                                    log::warn("obj", "Malformed entry: malformed number for v", &[line(), ("operator", operator.to_string())]);
                                     continue;
                                 },
                                 Ok(val) => val,
@@ -121,7 +125,7 @@ impl Model {
                     for triplet in entry_iter {
                         match Self::parse_face_triplet(triplet) {
                             None => {
-                                eprintln!("Encountered malformed triplet in f operator!");
+                                log::warn("obj", "Malformed vertex in face", &[line(), ("vertex", triplet.to_string())]);
                             },
                             Some(val) => {
                                 triplets.push(val);
@@ -130,26 +134,26 @@ impl Model {
                     }
                     // only support faces with *exactly* three vertices. yeah, i know.
                     if triplets.len() != 3 {
-                        eprintln!("Encountered face with unsupported vertex count!");
+                        log::warn("obj", "Skipping face: only triangles are supported", &[line(), ("vertices", triplets.len().to_string())]);
                         continue;
                     }
-                    let mut v0_index = triplets.get(0).unwrap().0;
+                    let mut v0_index = triplets.first().unwrap().0;
                     if v0_index < 0 {
-                        v0_index = geometric_vertices.len() as isize + v0_index;
+                        v0_index += geometric_vertices.len() as isize;
                     } else {
-                        v0_index = v0_index - 1;
+                        v0_index -= 1;
                     }
                     let mut v1_index = triplets.get(1).unwrap().0;
                     if v1_index < 0 {
-                        v1_index = geometric_vertices.len() as isize + v1_index;
+                        v1_index += geometric_vertices.len() as isize;
                     } else {
-                        v1_index = v1_index - 1;
+                        v1_index -= 1;
                     }
                     let mut v2_index = triplets.get(2).unwrap().0;
                     if v2_index < 0 {
-                        v2_index = geometric_vertices.len() as isize + v2_index;
+                        v2_index += geometric_vertices.len() as isize;
                     } else {
-                        v2_index = v2_index - 1;
+                        v2_index -= 1;
                     }
                     let mut triangle = Triangle {
                         v0: geometric_vertices.get(v0_index as usize).unwrap().clone(),
@@ -157,19 +161,31 @@ impl Model {
                         v2: geometric_vertices.get(v2_index as usize).unwrap().clone(),
                         material: material.clone(),
                         custom_normal: None,
+                        texture_coordinates: None,
                     };
-                    if let Some(vn0) = triplets.get(0).unwrap().2 {
+                    let texture_vertex = |index: Option<isize>| {
+                        let index = index?;
+                        let index = if index < 0 { texture_vertices.len() as isize + index } else { index - 1 };
+                        if index < 0 {
+                            return None;
+                        }
+                        texture_vertices.get(index as usize).copied()
+                    };
+                    if let (Some(vt0), Some(vt1), Some(vt2)) = (texture_vertex(triplets[0].1), texture_vertex(triplets[1].1), texture_vertex(triplets[2].1)) {
+                        triangle.texture_coordinates = Some([vt0, vt1, vt2]);
+                    }
+                    if let Some(vn0) = triplets.first().unwrap().2 {
                         if let Some(vn1) = triplets.get(1).unwrap().2 {
                             if let Some(vn2) = triplets.get(2).unwrap().2 {
                                 if vn0 != vn1 || vn1 != vn2 {
-                                    eprintln!("Unsupported geometry in OBJ file: Multiple normals for face!");
+                                    log::warn("obj", "Skipping face: faces with more than one normal are not supported", &[line()]);
                                     continue
                                 }
                                 let mut vn0 = vn0;
                                 if vn0 < 0 {
                                     vn0 = vertex_normals.len() as isize + v0_index;
                                 } else {
-                                    vn0 = vn0 - 1;
+                                    vn0 -= 1;
                                 }
                                 triangle.custom_normal = Some(vertex_normals.get(vn0 as usize).unwrap().unit_vector());
                             }
@@ -178,7 +194,7 @@ impl Model {
                     faces.push(Arc::new(triangle));
                 },
                 _ => {
-                    eprintln!("Ignoring unknown operator {} in OBJ!", operator);
+                    log::warn("obj", "Ignoring unknown operator", &[line(), ("operator", operator.to_string())]);
                     continue;
                 },
             }
@@ -190,8 +206,8 @@ impl Model {
 }
 
 impl Hittable for Model {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        self.faces.hit(ray, t_min, t_max)
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, sampler: &mut dyn Sampler) -> Option<HitRecord> {
+        self.faces.hit(ray, t_min, t_max, sampler)
     }
 
     fn bounding_box(&self, time_start: f64, time_end: f64) -> Option<AABB> {
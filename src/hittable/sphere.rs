@@ -4,6 +4,8 @@ use std::f64::consts;
 use crate::{hittable::{HitRecord, Hittable, AABB}, material::Material, vec3::Vec3};
 use crate::ray::Ray;
 use crate::vec3::Point3;
+use crate::sampler::Sampler;
+use crate::stats;
 
 pub struct Sphere {
     pub center: Point3,
@@ -22,7 +24,8 @@ impl Sphere {
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _: &mut dyn Sampler) -> Option<HitRecord> {
+        stats::count_primitive_test();
         let oc = &ray.origin - &self.center;
         let a = ray.direction.length_squared();
         let half_b = oc.dot(&ray.direction);
@@ -48,6 +51,15 @@ impl Hittable for Sphere {
         let outward_normal = (&hit_record.p - &self.center) / self.radius;
         hit_record.set_face_normal(ray, &outward_normal);
         Self::get_sphere_uv(&outward_normal, &mut hit_record.u, &mut hit_record.v);
+        // Around the y axis for u, and from pole to pole for v, which makes them vanish at the poles.
+        let n = &outward_normal;
+        let ring_radius = (n.x * n.x + n.z * n.z).sqrt();
+        hit_record.tangent = 2.0 * consts::PI * self.radius * Vec3 { x: n.z, y: 0.0, z: -n.x };
+        hit_record.bitangent = if ring_radius > 0.0 {
+            consts::PI * self.radius / ring_radius * Vec3 { x: -n.x * n.y, y: ring_radius * ring_radius, z: -n.y * n.z }
+        } else {
+            Vec3::new()
+        };
         hit_record.material = Some(self.material.clone());
         Some(hit_record)
     }
@@ -59,3 +71,74 @@ impl Hittable for Sphere {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::test_util::{assert_close, assert_vec_close, check_hit_properties, material, ray, sampler};
+
+    fn unit_sphere() -> Sphere {
+        Sphere { center: Point3::new(), radius: 1.0, material: material() }
+    }
+
+    #[test]
+    fn hits_front_from_outside() {
+        let hit_record = unit_sphere().hit(&ray((0.0, 0.0, -5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 4.0);
+        assert_vec_close(&hit_record.p, &Point3 { x: 0.0, y: 0.0, z: -1.0 });
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 0.0, y: 0.0, z: -1.0 });
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn hits_back_from_inside() {
+        let hit_record = unit_sphere().hit(&ray((0.0, 0.0, 0.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 1.0);
+        assert_vec_close(&hit_record.p, &Point3 { x: 0.0, y: 0.0, z: 1.0 });
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 0.0, y: 0.0, z: -1.0 });
+        assert!(!hit_record.front_face);
+    }
+
+    #[test]
+    fn grazing_ray_touches_once() {
+        let hit_record = unit_sphere().hit(&ray((-5.0, 1.0, 0.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 5.0);
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+        assert!(unit_sphere().hit(&ray((-5.0, 1.0001, 0.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn misses_behind_the_ray() {
+        assert!(unit_sphere().hit(&ray((0.0, 0.0, 5.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn respects_t_range() {
+        let sphere = unit_sphere();
+        let ray = ray((0.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        assert!(sphere.hit(&ray, 0.001, 3.9, &mut sampler()).is_none());
+        // Past the near side, the far side is the hit.
+        let hit_record = sphere.hit(&ray, 4.1, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 6.0);
+        assert!(!hit_record.front_face);
+        assert!(sphere.hit(&ray, 6.1, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn uv_covers_the_sphere() {
+        let (mut u, mut v) = (0.0, 0.0);
+        Sphere::get_sphere_uv(&Point3 { x: 0.0, y: -1.0, z: 0.0 }, &mut u, &mut v);
+        assert_close(v, 0.0);
+        Sphere::get_sphere_uv(&Point3 { x: 0.0, y: 1.0, z: 0.0 }, &mut u, &mut v);
+        assert_close(v, 1.0);
+        Sphere::get_sphere_uv(&Point3 { x: -1.0, y: 0.0, z: 0.0 }, &mut u, &mut v);
+        assert_close(u, 0.0);
+        assert_close(v, 0.5);
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        let sphere = Sphere { center: Point3 { x: 1.0, y: 2.0, z: -3.0 }, radius: 2.5, material: material() };
+        assert!(check_hit_properties(&sphere) > 100);
+    }
+}
@@ -0,0 +1,107 @@
+//! Helpers shared by the intersection tests of the individual primitives.
+
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::hittable::{Hittable, AABB};
+use crate::material::{Lambertian, Material};
+use crate::ray::Ray;
+use crate::spectrum::Wavelengths;
+use crate::sampler::IndependentSampler;
+use crate::vec3::{Color, Point3, Vec3};
+
+// Random rays tried by each property check.
+const PROPERTY_CASES: usize = 2000;
+// Allowed error when comparing positions and lengths.
+pub const EPSILON: f64 = 1e-9;
+
+pub fn sampler() -> IndependentSampler {
+    IndependentSampler::new(1, 0)
+}
+
+pub fn material() -> Arc<dyn Material> {
+    Arc::new(Lambertian::from_color(Color { x: 0.5, y: 0.5, z: 0.5 }))
+}
+
+pub fn rng() -> StdRng {
+    StdRng::seed_from_u64(0x5eed)
+}
+
+pub fn ray(origin: (f64, f64, f64), direction: (f64, f64, f64)) -> Ray {
+    Ray {
+        origin: Point3 { x: origin.0, y: origin.1, z: origin.2 },
+        direction: Vec3 { x: direction.0, y: direction.1, z: direction.2 },
+        time: 0.0,
+        wavelengths: Wavelengths::Rgb,
+    }
+}
+
+pub fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-6, "{} is not close to {}", a, b);
+}
+
+pub fn assert_vec_close(a: &Vec3, b: &Vec3) {
+    assert!((a - b).length() < 1e-6, "({}) is not close to ({})", a, b);
+}
+
+pub fn box_contains(aabb: &AABB, p: &Point3, tolerance: f64) -> bool {
+    (0..3).all(|axis| {
+        let value = *p.get(axis).unwrap();
+        value >= aabb.minimum.get(axis).unwrap() - tolerance && value <= aabb.maximum.get(axis).unwrap() + tolerance
+    })
+}
+
+/// A ray from somewhere around `aabb` towards a random point inside it (or a little outside, for some misses),
+/// at a random time in `[0, 1]`. Some rays start inside the box.
+fn random_ray(rng: &mut StdRng, aabb: &AABB) -> Ray {
+    let center = 0.5 * (&aabb.minimum + &aabb.maximum);
+    let extent = &aabb.maximum - &aabb.minimum;
+    let size = extent.length().max(1.0);
+    let random_point_near_box = |rng: &mut StdRng, margin: f64| Point3 {
+        x: aabb.minimum.x - margin * extent.x + rng.gen::<f64>() * (1.0 + 2.0 * margin) * extent.x,
+        y: aabb.minimum.y - margin * extent.y + rng.gen::<f64>() * (1.0 + 2.0 * margin) * extent.y,
+        z: aabb.minimum.z - margin * extent.z + rng.gen::<f64>() * (1.0 + 2.0 * margin) * extent.z,
+    };
+    let origin = if rng.gen::<f64>() < 0.2 {
+        random_point_near_box(rng, 0.0)
+    } else {
+        &center + 2.0 * size * Vec3::random_in_range(rng, -1.0, 1.0).unit_vector()
+    };
+    let target = random_point_near_box(rng, 0.1);
+    Ray {
+        direction: (&target - &origin) * (0.1 + 10.0 * rng.gen::<f64>()),
+        origin,
+        time: rng.gen::<f64>(),
+        wavelengths: Wavelengths::Rgb,
+    }
+}
+
+/// Shoots random rays at `hittable` and checks that every hit lies within `[t_min, t_max]`, at `ray.at(t)`,
+/// inside the reported bounding box for the shutter interval `[0, 1]`, and has a unit length normal facing
+/// against the ray and tangents along the surface. Returns how many rays hit, so callers can check the test exercised something.
+pub fn check_hit_properties(hittable: &dyn Hittable) -> usize {
+    let mut rng = rng();
+    let mut sampler = sampler();
+    let aabb = hittable.bounding_box(0.0, 1.0).expect("Property checks need a bounding box!");
+    let mut hits = 0;
+    for _ in 0..PROPERTY_CASES {
+        let ray = random_ray(&mut rng, &aabb);
+        let t_min = if rng.gen::<f64>() < 0.5 { 0.001 } else { rng.gen::<f64>() };
+        let t_max = if rng.gen::<f64>() < 0.5 { f64::INFINITY } else { t_min + rng.gen::<f64>() * 2.0 };
+        if let Some(hit_record) = hittable.hit(&ray, t_min, t_max, &mut sampler) {
+            hits += 1;
+            assert!(hit_record.t >= t_min && hit_record.t <= t_max, "t = {} outside [{}, {}]", hit_record.t, t_min, t_max);
+            assert_vec_close(&hit_record.p, &ray.at(hit_record.t));
+            let tolerance = EPSILON * (1.0 + aabb.minimum.length().max(aabb.maximum.length()));
+            assert!(box_contains(&aabb, &hit_record.p, tolerance), "Hit point ({}) is outside the bounding box ({}) - ({})", hit_record.p, aabb.minimum, aabb.maximum);
+            assert_close(hit_record.normal.length(), 1.0);
+            assert!(hit_record.normal.dot(&ray.direction) <= EPSILON, "Normal ({}) does not face against the ray", hit_record.normal);
+            for tangent in [&hit_record.tangent, &hit_record.bitangent] {
+                assert!(tangent.dot(&hit_record.normal).abs() <= EPSILON * (1.0 + tangent.length()), "Tangent ({}) is not along the surface", tangent);
+            }
+            assert!(hit_record.material.is_some());
+        }
+    }
+    hits
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use crate::{hittable::{HitRecord, Hittable, AABB}, material::Material, ray::Ray, vec3::{Point3, Vec3}};
+use crate::sampler::Sampler;
+use crate::stats;
 
 pub struct Triangle {
     pub v0: Point3,
@@ -8,6 +10,8 @@ pub struct Triangle {
     pub v2: Point3,
     pub material: Arc<dyn Material>,
     pub custom_normal: Option<Vec3>,
+    /// The (u, v) of each vertex, for textures. Without them, u and v are the weights of `v1` and `v2`.
+    pub texture_coordinates: Option<[(f64, f64); 3]>,
 }
 
 impl Triangle {
@@ -18,7 +22,8 @@ impl Triangle {
 
 impl Hittable for Triangle {
     // https://en.wikipedia.org/wiki/M%C3%B6ller%E2%80%93Trumbore_intersection_algorithm
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _: &mut dyn Sampler) -> Option<HitRecord> {
+        stats::count_primitive_test();
         let epsilon: f64 = 0.0000001;
         let edge1 = &self.v1 - &self.v0;
         let edge2 = &self.v2 - &self.v0;
@@ -31,7 +36,7 @@ impl Hittable for Triangle {
         let f = 1.0 / a;
         let s = &ray.origin - &self.v0;
         let u = f * s.dot(&h);
-        if u < 0.0 || u > 1.0 {
+        if !(0.0..=1.0).contains(&u) {
             return None;
         }
         let q = s.cross(&edge1);
@@ -45,13 +50,21 @@ impl Hittable for Triangle {
             return None;
         }
         let mut hit_record = HitRecord::new();
-        hit_record.u = u;
-        hit_record.v = v;
+        let [uv0, uv1, uv2] = self.texture_coordinates.unwrap_or([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+        hit_record.u = (1.0 - u - v) * uv0.0 + u * uv1.0 + v * uv2.0;
+        hit_record.v = (1.0 - u - v) * uv0.1 + u * uv1.1 + v * uv2.1;
+        // Solving edge = du * tangent + dv * bitangent for both edges.
+        let (du1, dv1, du2, dv2) = (uv1.0 - uv0.0, uv1.1 - uv0.1, uv2.0 - uv0.0, uv2.1 - uv0.1);
+        let determinant = du1 * dv2 - du2 * dv1;
+        if determinant != 0.0 {
+            hit_record.tangent = (dv2 * &edge1 - dv1 * &edge2) / determinant;
+            hit_record.bitangent = (du1 * &edge2 - du2 * &edge1) / determinant;
+        }
         hit_record.t = t;
         hit_record.p = ray.at(t);
         // TODO: i don't love this, but it allows for custom surface normals from OBJ data.
         if let Some(normal) = &self.custom_normal {
-            hit_record.set_face_normal(ray, &normal);
+            hit_record.set_face_normal(ray, normal);
         } else {
             let outward_normal = edge2.cross(&edge1).unit_vector();
             hit_record.set_face_normal(ray, &outward_normal);
@@ -77,3 +90,114 @@ impl Hittable for Triangle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use crate::hittable::test_util::{assert_close, assert_vec_close, check_hit_properties, material, ray, rng, sampler};
+
+    fn triangle(v0: (f64, f64, f64), v1: (f64, f64, f64), v2: (f64, f64, f64)) -> Triangle {
+        Triangle {
+            v0: Point3 { x: v0.0, y: v0.1, z: v0.2 },
+            v1: Point3 { x: v1.0, y: v1.1, z: v1.2 },
+            v2: Point3 { x: v2.0, y: v2.1, z: v2.2 },
+            material: material(),
+            custom_normal: None,
+            texture_coordinates: None,
+        }
+    }
+
+    fn unit_triangle() -> Triangle {
+        triangle((0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0))
+    }
+
+    #[test]
+    fn hits_inside_with_barycentrics() {
+        let hit_record = unit_triangle().hit(&ray((0.25, 0.5, 1.0), (0.0, 0.0, -1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 1.0);
+        assert_close(hit_record.u, 0.25);
+        assert_close(hit_record.v, 0.5);
+        assert_vec_close(&hit_record.p, &Point3 { x: 0.25, y: 0.5, z: 0.0 });
+    }
+
+    #[test]
+    fn normal_faces_the_ray_from_either_side() {
+        let above = unit_triangle().hit(&ray((0.25, 0.25, 1.0), (0.0, 0.0, -1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        let below = unit_triangle().hit(&ray((0.25, 0.25, -1.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_vec_close(&above.normal, &Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+        assert_vec_close(&below.normal, &Vec3 { x: 0.0, y: 0.0, z: -1.0 });
+        assert_ne!(above.front_face, below.front_face);
+    }
+
+    #[test]
+    fn edges_and_vertices_are_inclusive() {
+        let triangle = unit_triangle();
+        for (x, y) in [(0.5, 0.5), (0.5, 0.0), (0.0, 0.5), (1.0, 0.0), (0.0, 0.0)] {
+            assert!(triangle.hit(&ray((x, y, 1.0), (0.0, 0.0, -1.0)), 0.001, f64::INFINITY, &mut sampler()).is_some(), "Missed ({}, {})", x, y);
+        }
+        for (x, y) in [(0.51, 0.51), (-0.01, 0.5), (0.5, -0.01), (1.01, 0.0)] {
+            assert!(triangle.hit(&ray((x, y, 1.0), (0.0, 0.0, -1.0)), 0.001, f64::INFINITY, &mut sampler()).is_none(), "Hit ({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn rays_parallel_to_the_plane_miss() {
+        let triangle = unit_triangle();
+        assert!(triangle.hit(&ray((0.25, 0.25, 1.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+        // Grazing along the surface itself.
+        assert!(triangle.hit(&ray((-1.0, 0.25, 0.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn respects_t_range() {
+        let triangle = unit_triangle();
+        let ray = ray((0.25, 0.25, 2.0), (0.0, 0.0, -1.0));
+        assert!(triangle.hit(&ray, 0.001, 1.9, &mut sampler()).is_none());
+        assert!(triangle.hit(&ray, 2.1, f64::INFINITY, &mut sampler()).is_none());
+        assert!(triangle.hit(&ray, 1.9, 2.1, &mut sampler()).is_some());
+    }
+
+    #[test]
+    fn degenerate_triangles_never_hit() {
+        let collinear = triangle((0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (2.0, 0.0, 0.0));
+        let point = triangle((1.0, 1.0, 1.0), (1.0, 1.0, 1.0), (1.0, 1.0, 1.0));
+        for (x, y, z) in [(0.5, 0.0, 1.0), (1.0, 1.0, 2.0), (0.0, 1.0, 0.0)] {
+            for direction in [(0.0, 0.0, -1.0), (0.0, -1.0, 0.0), (-1.0, -1.0, -1.0)] {
+                assert!(collinear.hit(&ray((x, y, z), direction), 0.001, f64::INFINITY, &mut sampler()).is_none());
+                assert!(point.hit(&ray((x, y, z), direction), 0.001, f64::INFINITY, &mut sampler()).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn custom_normal_is_used() {
+        let mut triangle = unit_triangle();
+        let normal = Vec3 { x: 0.0, y: 0.6, z: 0.8 };
+        triangle.custom_normal = Some(normal.clone());
+        let hit_record = triangle.hit(&ray((0.25, 0.25, 1.0), (0.0, 0.0, -1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_vec_close(&hit_record.normal, &normal);
+    }
+
+    #[test]
+    fn interpolates_texture_coordinates() {
+        let mut triangle = triangle((0.0, 0.0, 0.0), (2.0, 0.0, 0.0), (0.0, 2.0, 0.0));
+        // Upside down and squashed, to check the tangents follow u and v rather than the edges.
+        triangle.texture_coordinates = Some([(0.5, 1.0), (1.0, 1.0), (0.5, 0.0)]);
+        let hit_record = triangle.hit(&ray((0.5, 1.0, 1.0), (0.0, 0.0, -1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.u, 0.625);
+        assert_close(hit_record.v, 0.5);
+        assert_vec_close(&hit_record.tangent, &Vec3 { x: 4.0, y: 0.0, z: 0.0 });
+        assert_vec_close(&hit_record.bitangent, &Vec3 { x: 0.0, y: -2.0, z: 0.0 });
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        let mut rng = rng();
+        let mut coordinate = || rng.gen_range(-5.0..5.0);
+        for _ in 0..10 {
+            let triangle = triangle((coordinate(), coordinate(), coordinate()), (coordinate(), coordinate(), coordinate()), (coordinate(), coordinate(), coordinate()));
+            assert!(check_hit_properties(&triangle) > 0);
+        }
+    }
+}
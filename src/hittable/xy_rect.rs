@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use crate::{hittable::{HitRecord, Hittable, AABB}, material::Material, ray::Ray, vec3::{Point3, Vec3}};
+use crate::sampler::Sampler;
+use crate::stats;
 
 pub struct XYRect {
     pub material: Arc<dyn Material>,
@@ -18,9 +20,11 @@ impl XYRect {
 }
 
 impl Hittable for XYRect {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _: &mut dyn Sampler) -> Option<HitRecord> {
+        stats::count_primitive_test();
         let t = (self.k - ray.origin.z) / ray.direction.z;
-        if t < t_min || t > t_max {
+        // A ray lying in the plane gives NaN here, which fails this too.
+        if !(t_min..=t_max).contains(&t) {
             return None;
         }
         let x = ray.origin.x + t * ray.direction.x;
@@ -31,6 +35,8 @@ impl Hittable for XYRect {
         let mut hit_record = HitRecord::new();
         hit_record.u = (x - self.x0) / (self.x1 - self.x0);
         hit_record.v = (y - self.y0) / (self.y1 - self.y0);
+        hit_record.tangent = Vec3 { x: self.x1 - self.x0, y: 0.0, z: 0.0 };
+        hit_record.bitangent = Vec3 { x: 0.0, y: self.y1 - self.y0, z: 0.0 };
         hit_record.t = t;
         let outward_normal = Vec3 { x: 0.0, y: 0.0, z: 1.0 };
         hit_record.set_face_normal(ray, &outward_normal);
@@ -46,3 +52,65 @@ impl Hittable for XYRect {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::test_util::{assert_close, assert_vec_close, check_hit_properties, material, ray, sampler};
+
+    fn rect() -> XYRect {
+        XYRect { material: material(), x0: -1.0, x1: 1.0, y0: 0.0, y1: 2.0, k: 3.0 }
+    }
+
+    #[test]
+    fn hits_with_uv_and_facing_normal() {
+        let hit_record = rect().hit(&ray((0.5, 1.5, 0.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 3.0);
+        assert_close(hit_record.u, 0.75);
+        assert_close(hit_record.v, 0.75);
+        assert_vec_close(&hit_record.p, &Point3 { x: 0.5, y: 1.5, z: 3.0 });
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 0.0, y: 0.0, z: -1.0 });
+        assert!(!hit_record.front_face);
+        let hit_record = rect().hit(&ray((0.5, 1.5, 5.0), (0.0, 0.0, -1.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn edges_are_inclusive() {
+        let rect = rect();
+        assert!(rect.hit(&ray((1.0, 2.0, 0.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).is_some());
+        assert!(rect.hit(&ray((-1.0, 0.0, 0.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).is_some());
+        assert!(rect.hit(&ray((1.01, 1.0, 0.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+        assert!(rect.hit(&ray((0.0, -0.01, 0.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn rays_parallel_to_the_plane_miss() {
+        let rect = rect();
+        assert!(rect.hit(&ray((-5.0, 1.0, 0.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+        // Grazing along the rect itself.
+        assert!(rect.hit(&ray((-5.0, 1.0, 3.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn respects_t_range() {
+        let rect = rect();
+        let ray = ray((0.0, 1.0, 0.0), (0.0, 0.0, 1.0));
+        assert!(rect.hit(&ray, 0.001, 2.9, &mut sampler()).is_none());
+        assert!(rect.hit(&ray, 3.1, f64::INFINITY, &mut sampler()).is_none());
+        assert!(rect.hit(&ray, 2.9, 3.1, &mut sampler()).is_some());
+    }
+
+    #[test]
+    fn infinite_rects_have_no_bounding_box() {
+        let rect = XYRect { material: material(), x0: -f64::INFINITY, x1: f64::INFINITY, y0: 0.0, y1: 1.0, k: 0.0 };
+        assert!(rect.bounding_box(0.0, 1.0).is_none());
+        assert!(rect.hit(&ray((1e9, 0.5, -1.0), (0.0, 0.0, 1.0)), 0.001, f64::INFINITY, &mut sampler()).is_some());
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        assert!(check_hit_properties(&rect()) > 100);
+    }
+}
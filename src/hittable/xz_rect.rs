@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use crate::{hittable::{HitRecord, Hittable, AABB}, material::Material, ray::Ray, vec3::{Point3, Vec3}};
+use crate::sampler::Sampler;
+use crate::stats;
 
 pub struct XZRect {
     pub material: Arc<dyn Material>,
@@ -18,9 +20,11 @@ impl XZRect {
 }
 
 impl Hittable for XZRect {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _: &mut dyn Sampler) -> Option<HitRecord> {
+        stats::count_primitive_test();
         let t = (self.k - ray.origin.y) / ray.direction.y;
-        if t < t_min || t > t_max {
+        // A ray lying in the plane gives NaN here, which fails this too.
+        if !(t_min..=t_max).contains(&t) {
             return None;
         }
         let x = ray.origin.x + t * ray.direction.x;
@@ -31,6 +35,8 @@ impl Hittable for XZRect {
         let mut hit_record = HitRecord::new();
         hit_record.u = (x - self.x0) / (self.x1 - self.x0);
         hit_record.v = (z - self.z0) / (self.z1 - self.z0);
+        hit_record.tangent = Vec3 { x: self.x1 - self.x0, y: 0.0, z: 0.0 };
+        hit_record.bitangent = Vec3 { x: 0.0, y: 0.0, z: self.z1 - self.z0 };
         hit_record.t = t;
         let outward_normal = Vec3 { x: 0.0, y: 1.0, z: 0.0 };
         hit_record.set_face_normal(ray, &outward_normal);
@@ -46,3 +52,48 @@ impl Hittable for XZRect {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::test_util::{assert_close, assert_vec_close, check_hit_properties, material, ray, sampler};
+
+    fn rect() -> XZRect {
+        XZRect { material: material(), x0: -1.0, x1: 1.0, z0: 0.0, z1: 2.0, k: 3.0 }
+    }
+
+    #[test]
+    fn hits_with_uv_and_facing_normal() {
+        let hit_record = rect().hit(&ray((0.5, 5.0, 1.5), (0.0, -1.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 2.0);
+        assert_close(hit_record.u, 0.75);
+        assert_close(hit_record.v, 0.75);
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn edges_are_inclusive() {
+        let rect = rect();
+        assert!(rect.hit(&ray((1.0, 0.0, 2.0), (0.0, 1.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_some());
+        assert!(rect.hit(&ray((1.0, 0.0, 2.01), (0.0, 1.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn rays_parallel_to_the_plane_miss() {
+        assert!(rect().hit(&ray((-5.0, 3.0, 1.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn respects_t_range() {
+        let rect = rect();
+        let ray = ray((0.0, 0.0, 1.0), (0.0, 1.0, 0.0));
+        assert!(rect.hit(&ray, 0.001, 2.9, &mut sampler()).is_none());
+        assert!(rect.hit(&ray, 3.1, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        assert!(check_hit_properties(&rect()) > 100);
+    }
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use crate::{hittable::{HitRecord, Hittable, AABB}, material::Material, ray::Ray, vec3::{Point3, Vec3}};
+use crate::sampler::Sampler;
+use crate::stats;
 
 pub struct YZRect {
     pub material: Arc<dyn Material>,
@@ -18,9 +20,11 @@ impl YZRect {
 }
 
 impl Hittable for YZRect {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _: &mut dyn Sampler) -> Option<HitRecord> {
+        stats::count_primitive_test();
         let t = (self.k - ray.origin.x) / ray.direction.x;
-        if t < t_min || t > t_max {
+        // A ray lying in the plane gives NaN here, which fails this too.
+        if !(t_min..=t_max).contains(&t) {
             return None;
         }
         let y = ray.origin.y + t * ray.direction.y;
@@ -31,6 +35,8 @@ impl Hittable for YZRect {
         let mut hit_record = HitRecord::new();
         hit_record.u = (y - self.y0) / (self.y1 - self.y0);
         hit_record.v = (z - self.z0) / (self.z1 - self.z0);
+        hit_record.tangent = Vec3 { x: 0.0, y: self.y1 - self.y0, z: 0.0 };
+        hit_record.bitangent = Vec3 { x: 0.0, y: 0.0, z: self.z1 - self.z0 };
         hit_record.t = t;
         let outward_normal = Vec3 { x: 1.0, y: 0.0, z: 0.0 };
         hit_record.set_face_normal(ray, &outward_normal);
@@ -46,3 +52,48 @@ impl Hittable for YZRect {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::test_util::{assert_close, assert_vec_close, check_hit_properties, material, ray, sampler};
+
+    fn rect() -> YZRect {
+        YZRect { material: material(), y0: -1.0, y1: 1.0, z0: 0.0, z1: 2.0, k: 3.0 }
+    }
+
+    #[test]
+    fn hits_with_uv_and_facing_normal() {
+        let hit_record = rect().hit(&ray((5.0, 0.5, 1.5), (-1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).unwrap();
+        assert_close(hit_record.t, 2.0);
+        assert_close(hit_record.u, 0.75);
+        assert_close(hit_record.v, 0.75);
+        assert_vec_close(&hit_record.normal, &Vec3 { x: 1.0, y: 0.0, z: 0.0 });
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn edges_are_inclusive() {
+        let rect = rect();
+        assert!(rect.hit(&ray((0.0, -1.0, 0.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_some());
+        assert!(rect.hit(&ray((0.0, -1.01, 0.0), (1.0, 0.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn rays_parallel_to_the_plane_miss() {
+        assert!(rect().hit(&ray((3.0, -5.0, 1.0), (0.0, 1.0, 0.0)), 0.001, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn respects_t_range() {
+        let rect = rect();
+        let ray = ray((0.0, 0.0, 1.0), (1.0, 0.0, 0.0));
+        assert!(rect.hit(&ray, 0.001, 2.9, &mut sampler()).is_none());
+        assert!(rect.hit(&ray, 3.1, f64::INFINITY, &mut sampler()).is_none());
+    }
+
+    #[test]
+    fn random_hits_are_consistent() {
+        assert!(check_hit_properties(&rect()) > 100);
+    }
+}
@@ -1,77 +1,157 @@
 use std::io::Write;
 
+use crate::spectrum::xyz_to_rgb;
 use crate::vec3::Color;
 
+/// What the pixel values of an `Image` mean.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Linear RGB, with the primaries and white point of sRGB.
+    #[default]
+    LinearSrgb,
+    /// CIE 1931 XYZ, from spectral renders.
+    Xyz,
+}
+
+/// A finished image in linear color, ready to be written out.
 pub struct Image {
     width: usize,
     height: usize,
-    data: Vec<Pixel>,
-}
-
-#[derive(Clone)]
-struct Pixel {
-    color: Color,
-    sample_count: u32,
+    color_space: ColorSpace,
+    data: Vec<Color>,
 }
 
 impl Image {
     pub fn new(width: usize, height: usize) -> Image {
-        let data = vec![
-            Pixel {
-                color: Color {
-                    x: 0.0,
-                    y: 0.0,
-                    z: 0.0
-                },
-                sample_count: 0
-            };
-            width * height
-        ];
         Image {
             width,
             height,
-            data,
+            color_space: ColorSpace::default(),
+            data: vec![Color::new(); width * height],
+        }
+    }
+
+    pub fn with_color_space(self, color_space: ColorSpace) -> Image {
+        Image { color_space, ..self }
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> &Color {
+        self.data.get((y * self.width) + x).unwrap()
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        *self.data.get_mut((y * self.width) + x).unwrap() = color;
+    }
+
+    /// The pixel in linear RGB, whatever the color space of the image.
+    pub fn pixel_rgb(&self, x: usize, y: usize) -> Color {
+        match self.color_space {
+            ColorSpace::LinearSrgb => self.pixel(x, y).clone(),
+            ColorSpace::Xyz => xyz_to_rgb(self.pixel(x, y)),
         }
     }
 
-    pub fn add_sample(&mut self, x: usize, y: usize, color: Color) {
-        self.data
-            .get_mut((y * self.width) + x)
-            .unwrap()
-            .update(color);
+    fn rgb8(&self, x: usize, y: usize) -> [u8; 3] {
+        let pixel = self.pixel_rgb(x, y);
+
+        // Perform gamma correction for gamma 2. Filters with negative lobes can leave slightly negative values.
+        let r = pixel.x.max(0.0).sqrt();
+        let g = pixel.y.max(0.0).sqrt();
+        let b = pixel.z.max(0.0).sqrt();
+
+        [
+            (256.0 * r.clamp(0.0, 0.999)) as u8,
+            (256.0 * g.clamp(0.0, 0.999)) as u8,
+            (256.0 * b.clamp(0.0, 0.999)) as u8,
+        ]
     }
 
     pub fn write(&self, output: &mut impl Write) {
         output.write_fmt(format_args!("P3\n{} {}\n255\n", self.width, self.height)).unwrap();
         for y in (0..self.height).rev() {
             for x in 0..self.width {
-                let pixel = self.data.get((y * self.width) + x).unwrap();
-                let mut r = pixel.color.x;
-                let mut g = pixel.color.y;
-                let mut b = pixel.color.z;
-
-                // Divide by the number of samples and perform gamma correction for gamma 2
-                let scale = 1.0 / pixel.sample_count as f64;
-                r = (r * scale).sqrt();
-                g = (g * scale).sqrt();
-                b = (b * scale).sqrt();
-
-                output
-                    .write_fmt(format_args!(
-                        "{} {} {}\n",
-                        (256.0 * r.clamp(0.0, 0.999)) as u32,
-                        (256.0 * g.clamp(0.0, 0.999)) as u32,
-                        (256.0 * b.clamp(0.0, 0.999)) as u32,
-                    ))
-                    .unwrap();
+                let [r, g, b] = self.rgb8(x, y);
+                output.write_fmt(format_args!("{} {} {}\n", r, g, b)).unwrap();
+            }
+        }
+    }
+
+    // https://www.w3.org/TR/png/
+    // The image data is zlib-wrapped but not compressed, using stored deflate blocks.
+    pub fn write_png(&self, output: &mut impl Write) -> std::io::Result<()> {
+        let mut raw = Vec::with_capacity(self.height * (1 + 3 * self.width));
+        for y in (0..self.height).rev() {
+            raw.push(0); // filter type: none
+            for x in 0..self.width {
+                raw.extend_from_slice(&self.rgb8(x, y));
             }
         }
+
+        let mut zlib = vec![0x78, 0x01];
+        let mut blocks = raw.chunks(0xFFFF).peekable();
+        if blocks.peek().is_none() {
+            zlib.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+        }
+        while let Some(block) = blocks.next() {
+            let length = block.len() as u16;
+            zlib.push(if blocks.peek().is_none() { 1 } else { 0 });
+            zlib.extend_from_slice(&length.to_le_bytes());
+            zlib.extend_from_slice(&(!length).to_le_bytes());
+            zlib.extend_from_slice(block);
+        }
+        zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // 8 bits per channel, truecolor, default compression and filtering, no interlacing
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        output.write_all(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A])?;
+        write_png_chunk(output, b"IHDR", &header)?;
+        write_png_chunk(output, b"IDAT", &zlib)?;
+        write_png_chunk(output, b"IEND", &[])
+    }
+}
+
+fn write_png_chunk(output: &mut impl Write, chunk_type: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    output.write_all(&(data.len() as u32).to_be_bytes())?;
+    output.write_all(chunk_type)?;
+    output.write_all(data)?;
+    let mut crc = crc32_update(0xFFFFFFFF, chunk_type);
+    crc = crc32_update(crc, data);
+    output.write_all(&(!crc).to_be_bytes())
+}
+
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = crc;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { 0xEDB88320 ^ (crc >> 1) } else { crc >> 1 };
+        }
     }
+    crc
 }
 
-impl Pixel {
-    pub fn update(&mut self, color: Color) {
-        self.color += color;
-        self.sample_count += 1;
+fn adler32(data: &[u8]) -> u32 {
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
     }
+    (b << 16) | a
 }
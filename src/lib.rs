@@ -0,0 +1,72 @@
+//! A path tracer following _Ray Tracing in One Weekend_ and _Ray Tracing: The Next Week_.
+//!
+//! Build a [`Scene`] from the objects in [`hittable`] and materials in [`material`], point a camera from
+//! [`camera`] at it, and hand both to [`render`] with some [`RenderSettings`]:
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use ray_tracing_in_one_weekend::{render, RenderSettings, Scene};
+//! use ray_tracing_in_one_weekend::hittable::{HittableList, Sphere};
+//! use ray_tracing_in_one_weekend::material::Lambertian;
+//! use ray_tracing_in_one_weekend::vec3::{Color, Point3};
+//!
+//! let mut world = HittableList::new();
+//! let material = Arc::new(Lambertian::from_color(Color { x: 0.5, y: 0.5, z: 0.5 }));
+//! world.add(Arc::new(Sphere { center: Point3 { x: 0.0, y: 0.0, z: -1.0 }, radius: 0.5, material }));
+//! let scene = Scene {
+//!     settings: RenderSettings { image_width: 32, image_height: 18, samples_per_pixel: 4, ..Default::default() },
+//!     ..Scene::new(Arc::new(world), Point3::new(), Point3 { x: 0.0, y: 0.0, z: -1.0 }, 90.0)
+//! };
+//!
+//! let image = render(&scene, Arc::new(scene.perspective_camera(0.0, 1.0)), &scene.settings);
+//! assert_eq!((image.width(), image.height()), (32, 18));
+//! // image.write(&mut std::io::stdout()) writes a PPM, image.write_png(...) a PNG.
+//! ```
+//!
+//! Renders are deterministic: the same scene, camera and settings always give the same image.
+
+/// Camera paths and frame timing for animations.
+pub mod animation;
+/// Cameras, lenses and shutters that turn image positions into rays.
+pub mod camera;
+/// Accumulates samples into pixels through a reconstruction filter.
+pub mod film;
+/// Reconstruction filters for the film.
+pub mod filter;
+/// Geometry, acceleration structures and instancing.
+pub mod hittable;
+/// Finished images, and writing them as PPM or PNG.
+pub mod image;
+/// Structured warnings from the library, and where they go.
+pub mod log;
+/// How surfaces and media scatter and emit light.
+pub mod material;
+/// 4x4 matrices for transforms.
+pub mod matrix4;
+/// Progress reporting while rendering.
+pub mod progress;
+/// Quaternions for interpolating rotations.
+pub mod quaternion;
+pub mod ray;
+/// Sample generators for pixels, lenses, shutters and materials.
+pub mod sampler;
+/// The built-in scenes, shared by the binary and the examples so they render the same thing. They are here to show
+/// what the renderer can do, and may change without notice.
+pub mod scenes;
+/// Wavelengths, color matching and the conversions between spectra and RGB.
+pub mod spectrum;
+/// Counts of the work a render does.
+pub mod stats;
+/// Textures for material parameters.
+pub mod texture;
+pub mod util;
+/// Vectors, points and colors.
+pub mod vec3;
+mod render;
+mod scene;
+
+pub use camera::Camera;
+pub use image::Image;
+pub use render::{render, render_with_progress, RenderSettings};
+pub use scene::Scene;
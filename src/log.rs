@@ -0,0 +1,84 @@
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use crate::util::json_string;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Info,
+    Warning,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Level::Info => "info",
+            Level::Warning => "warning",
+        })
+    }
+}
+
+/// Something the library has to say, with the details as fields so they can be filtered and aggregated.
+pub struct Event<'a> {
+    pub level: Level,
+    /// The part of the library the event comes from, like `obj`.
+    pub target: &'static str,
+    pub message: &'a str,
+    pub fields: &'a [(&'static str, String)],
+}
+
+/// Receives every event the library logs. Install one with `set_logger`.
+pub trait Logger: Send + Sync {
+    fn log(&self, event: &Event);
+}
+
+/// Writes events to stderr as `warning [obj] message (key=value, ...)`. Used until another logger is set.
+pub struct TextLogger;
+
+impl Logger for TextLogger {
+    fn log(&self, event: &Event) {
+        let fields: Vec<String> = event.fields.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+        if fields.is_empty() {
+            eprintln!("{} [{}] {}", event.level, event.target, event.message);
+        } else {
+            eprintln!("{} [{}] {} ({})", event.level, event.target, event.message, fields.join(", "));
+        }
+    }
+}
+
+/// Writes events to stderr as JSON, one object per line.
+pub struct JsonLogger;
+
+impl Logger for JsonLogger {
+    fn log(&self, event: &Event) {
+        let mut line = format!(
+            "{{\"level\":{},\"target\":{},\"message\":{}",
+            json_string(&event.level.to_string()), json_string(event.target), json_string(event.message)
+        );
+        for (key, value) in event.fields {
+            line.push_str(&format!(",{}:{}", json_string(key), json_string(value)));
+        }
+        line.push('}');
+        eprintln!("{}", line);
+    }
+}
+
+static LOGGER: RwLock<Option<Arc<dyn Logger>>> = RwLock::new(None);
+
+/// Sends all events from now on to `logger`, from every thread.
+pub fn set_logger(logger: Arc<dyn Logger>) {
+    *LOGGER.write().unwrap() = Some(logger);
+}
+
+pub(crate) fn log(level: Level, target: &'static str, message: &str, fields: &[(&'static str, String)]) {
+    let event = Event { level, target, message, fields };
+    match LOGGER.read().unwrap().as_ref() {
+        Some(logger) => logger.log(&event),
+        None => TextLogger.log(&event),
+    }
+}
+
+/// Logs a warning to the logger set with `set_logger`, or to stderr as text if there is none.
+pub fn warn(target: &'static str, message: &str, fields: &[(&'static str, String)]) {
+    log(Level::Warning, target, message, fields);
+}
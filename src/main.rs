@@ -1,115 +1,369 @@
-mod camera;
-mod hittable;
-mod material;
-mod ray;
-mod util;
-mod vec3;
-mod image;
-mod texture;
-mod scenes;
+use std::{fs::File, io::{BufWriter, Write}, path::{Path, PathBuf}, sync::Arc};
 
-use std::{sync::{Arc, mpsc}, thread};
+use ray_tracing_in_one_weekend::{render_with_progress, RenderSettings, Scene};
+use ray_tracing_in_one_weekend::log::{self, set_logger, JsonLogger, Logger, TextLogger};
+use ray_tracing_in_one_weekend::progress::ConsoleProgress;
+use ray_tracing_in_one_weekend::camera::{autofocus, ApertureMask, ApertureShape, Camera, EquirectangularCamera, Lens, FisheyeCamera, FisheyeMapping, OrthographicCamera, PerspectiveCamera, Shutter, ShutterCurve};
+use ray_tracing_in_one_weekend::filter::{BoxFilter, Filter, GaussianFilter, LanczosFilter, MitchellFilter, TentFilter};
+use ray_tracing_in_one_weekend::texture::ImageTexture;
+use ray_tracing_in_one_weekend::sampler::SamplerKind;
+use ray_tracing_in_one_weekend::animation::{CameraKeyframe, FrameTiming};
+use ray_tracing_in_one_weekend::util;
+use ray_tracing_in_one_weekend::scenes::get_scene;
 
-use camera::Camera;
-use hittable::Hittable;
-use image::Image;
-use ray::Ray;
-use vec3::{Vec3, Color};
-use scenes::get_scene;
+#[derive(Clone, Copy)]
+enum Projection {
+    Perspective,
+    Orthographic,
+    FisheyeEquidistant,
+    FisheyeEquisolid,
+    Equirectangular,
+}
 
-struct PixelUpdate {
-    color: Color,
-    x: usize,
-    y: usize,
+impl Projection {
+    /// The aspect ratio images need for this projection, given the one the scene asks for.
+    fn aspect_ratio(&self, scene_aspect_ratio: f64) -> f64 {
+        match self {
+            Projection::Equirectangular => 2.0,
+            _ => scene_aspect_ratio,
+        }
+    }
 }
 
-fn ray_color(ray: &Ray, background: &Color, world: &dyn Hittable, depth: u32) -> Color {
-    if depth <= 0 {
-        return Color {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-        };
+struct Options {
+    scene: u32,
+    projection: Projection,
+    shutter: Shutter,
+    frames: Option<u32>,
+    frames_per_second: f64,
+    shutter_angle: f64,
+    output_dir: PathBuf,
+    // Pixel to focus on, counted from the top left corner.
+    autofocus: Option<(f64, f64)>,
+    focal_length: Option<f64>,
+    f_stop: Option<f64>,
+    units_per_meter: f64,
+    aperture_shape: ApertureShape,
+    tilt: f64,
+    swing: f64,
+    shift: (f64, f64),
+    filter: Arc<dyn Filter>,
+    sampler: SamplerKind,
+    seed: u64,
+    spectral: bool,
+    // These override the scene's own settings.
+    image_width: Option<u32>,
+    samples_per_pixel: Option<u32>,
+    // Where to write render statistics as JSON, one line per frame for image sequences.
+    stats_json: Option<PathBuf>,
+    logger: Arc<dyn Logger>,
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("Usage: ray-tracing-in-one-weekend [scene] [--camera perspective|orthographic|fisheye-equidistant|fisheye-equisolid|equirectangular]");
+    eprintln!("                                  [--shutter box|triangle|<openness,openness,...>] [--rolling-shutter <readout>]");
+    eprintln!("                                  [--frames <count> [--fps <rate>] [--shutter-angle <degrees>] [--output-dir <dir>]]");
+    eprintln!("                                  [--autofocus <x,y>] [--focal-length <mm>] [--f-stop <number>] [--units-per-meter <units>]");
+    eprintln!("                                  [--aperture-blades <count> [--aperture-rotation <degrees>] | --aperture-mask <bmp>]");
+    eprintln!("                                  [--tilt <degrees>] [--swing <degrees>] [--shift <x,y>]");
+    eprintln!("                                  [--filter box|tent|gaussian|mitchell|lanczos [--filter-radius <pixels>]]");
+    eprintln!("                                  [--sampler independent|stratified|halton|sobol] [--seed <number>] [--spectral]");
+    eprintln!("                                  [--width <pixels>] [--samples <count>]");
+    eprintln!("                                  [--stats-json <file>] [--log-format text|json]");
+    std::process::exit(1);
+}
+
+fn parse_shutter_curve(value: &str) -> ShutterCurve {
+    match value {
+        "box" => ShutterCurve::Box,
+        "triangle" => ShutterCurve::Triangle,
+        _ => ShutterCurve::Custom(value.split(',').map(|openness| match openness.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() => value,
+            _ => usage_error(&format!("Malformed shutter curve value {}!", openness)),
+        }).collect()),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    value.and_then(|value| value.trim().parse::<T>().ok()).unwrap_or_else(|| usage_error(&format!("Missing or malformed value for {}!", flag)))
+}
+
+fn parse_pair(flag: &str, value: Option<String>) -> (f64, f64) {
+    let value = value.unwrap_or_else(|| usage_error(&format!("Missing value for {}!", flag)));
+    let mut parts = value.split(',').map(|part| part.trim().parse::<f64>().ok());
+    match (parts.next().flatten(), parts.next().flatten(), parts.next()) {
+        (Some(a), Some(b), None) => (a, b),
+        _ => usage_error(&format!("Malformed value for {}, expected two comma-separated numbers!", flag)),
     }
-    match world.hit(ray, 0.001, f64::INFINITY) {
-        None => background.clone(),
-        Some(rec) => {
-            let mut scattered = Ray::new();
-            let mut attenuation = Color::new();
-            if let Some(material) = &rec.material {
-                let emitted = material.emitted(rec.u, rec.v, &rec.p);
-                if !material.scatter(&ray, &rec, &mut attenuation, &mut scattered) {
-                    emitted
-                } else {
-                    emitted + attenuation * ray_color(&scattered, background, world, depth - 1)
+}
+
+fn parse_options() -> Options {
+    let mut scene = 0;
+    let mut projection = Projection::Perspective;
+    let mut curve = ShutterCurve::Box;
+    let mut readout = 0.0;
+    let mut frames = None;
+    let mut frames_per_second = 24.0;
+    let mut shutter_angle = 180.0;
+    let mut output_dir = PathBuf::from(".");
+    let mut autofocus = None;
+    let mut focal_length = None;
+    let mut f_stop = None;
+    let mut units_per_meter = 1.0;
+    let mut aperture_blades = None;
+    let mut aperture_rotation = 0.0;
+    let mut aperture_mask = None;
+    let mut tilt = 0.0;
+    let mut swing = 0.0;
+    let mut shift = (0.0, 0.0);
+    let mut filter_name = String::from("box");
+    let mut filter_radius = None;
+    let mut sampler = SamplerKind::Sobol;
+    let mut seed = 0;
+    let mut spectral = false;
+    let mut image_width = None;
+    let mut samples_per_pixel = None;
+    let mut stats_json = None;
+    let mut logger: Arc<dyn Logger> = Arc::new(TextLogger);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--camera" => projection = match args.next().as_deref() {
+                Some("perspective") => Projection::Perspective,
+                Some("orthographic") => Projection::Orthographic,
+                Some("fisheye-equidistant") => Projection::FisheyeEquidistant,
+                Some("fisheye-equisolid") => Projection::FisheyeEquisolid,
+                Some("equirectangular") => Projection::Equirectangular,
+                _ => usage_error("Missing or unknown value for --camera!"),
+            },
+            "--frames" => frames = Some(parse_number("--frames", args.next())),
+            "--fps" => frames_per_second = parse_number("--fps", args.next()),
+            "--shutter-angle" => shutter_angle = parse_number("--shutter-angle", args.next()),
+            "--output-dir" => output_dir = PathBuf::from(args.next().unwrap_or_else(|| usage_error("Missing value for --output-dir!"))),
+            "--shutter" => curve = parse_shutter_curve(&args.next().unwrap_or_else(|| usage_error("Missing value for --shutter!"))),
+            "--rolling-shutter" => {
+                readout = parse_number("--rolling-shutter", args.next());
+                if !(0.0..1.0).contains(&readout) {
+                    usage_error("Rolling shutter readout must be in [0.0, 1.0)!");
                 }
-            } else {
-                Color { x: 0.0, y: 0.0, z: 0.0 }
-            }
-        },
+            },
+            "--autofocus" => autofocus = Some(parse_pair("--autofocus", args.next())),
+            "--focal-length" => focal_length = Some(parse_number("--focal-length", args.next())),
+            "--f-stop" => f_stop = Some(parse_number("--f-stop", args.next())),
+            "--units-per-meter" => units_per_meter = parse_number("--units-per-meter", args.next()),
+            "--aperture-blades" => aperture_blades = Some(parse_number("--aperture-blades", args.next())),
+            "--aperture-rotation" => aperture_rotation = parse_number("--aperture-rotation", args.next()),
+            "--aperture-mask" => {
+                let path = args.next().unwrap_or_else(|| usage_error("Missing value for --aperture-mask!"));
+                let data = std::fs::read(&path).unwrap_or_else(|err| usage_error(&format!("Could not read {}: {}", path, err)));
+                aperture_mask = Some(ImageTexture::from_bmp_data(&data));
+            },
+            "--tilt" => tilt = parse_number("--tilt", args.next()),
+            "--swing" => swing = parse_number("--swing", args.next()),
+            "--shift" => shift = parse_pair("--shift", args.next()),
+            "--filter" => filter_name = args.next().unwrap_or_else(|| usage_error("Missing value for --filter!")),
+            "--filter-radius" => filter_radius = Some(parse_number("--filter-radius", args.next())),
+            "--sampler" => sampler = match args.next().as_deref() {
+                Some("independent") => SamplerKind::Independent,
+                Some("stratified") => SamplerKind::Stratified,
+                Some("halton") => SamplerKind::Halton,
+                Some("sobol") => SamplerKind::Sobol,
+                _ => usage_error("Missing or unknown value for --sampler!"),
+            },
+            "--seed" => seed = parse_number("--seed", args.next()),
+            "--spectral" => spectral = true,
+            "--width" => image_width = Some(parse_number("--width", args.next())),
+            "--samples" => samples_per_pixel = Some(parse_number("--samples", args.next())),
+            "--stats-json" => stats_json = Some(PathBuf::from(args.next().unwrap_or_else(|| usage_error("Missing value for --stats-json!")))),
+            "--log-format" => logger = match args.next().as_deref() {
+                Some("text") => Arc::new(TextLogger),
+                Some("json") => Arc::new(JsonLogger),
+                _ => usage_error("Missing or unknown value for --log-format!"),
+            },
+            _ => scene = arg.trim().parse().unwrap_or_else(|_| usage_error(&format!("Unknown argument {}!", arg))),
+        }
+    }
+    if frames_per_second <= 0.0 {
+        usage_error("--fps must be positive!");
+    }
+    if !(0.0..=360.0).contains(&shutter_angle) {
+        usage_error("--shutter-angle must be in [0.0, 360.0]!");
+    }
+    if focal_length.is_some_and(|focal_length| focal_length <= 0.0) || f_stop.is_some_and(|f_stop| f_stop <= 0.0) {
+        usage_error("--focal-length and --f-stop must be positive!");
+    }
+    if image_width.is_some_and(|width| width < 2) || samples_per_pixel == Some(0) {
+        usage_error("--width must be at least 2 and --samples at least 1!");
+    }
+    if filter_radius.is_some_and(|radius: f64| radius <= 0.0) {
+        usage_error("--filter-radius must be positive!");
+    }
+    let filter: Arc<dyn Filter> = match (filter_name.as_str(), filter_radius) {
+        ("box", radius) => Arc::new(radius.map(BoxFilter::new).unwrap_or_default()),
+        ("tent", radius) => Arc::new(radius.map(TentFilter::new).unwrap_or_default()),
+        ("gaussian", Some(radius)) => Arc::new(GaussianFilter::new(radius, radius / 3.0)),
+        ("gaussian", None) => Arc::new(GaussianFilter::default()),
+        ("mitchell", Some(radius)) => Arc::new(MitchellFilter::new(radius, 1.0 / 3.0, 1.0 / 3.0)),
+        ("mitchell", None) => Arc::new(MitchellFilter::default()),
+        ("lanczos", Some(radius)) => Arc::new(LanczosFilter::new(radius, radius)),
+        ("lanczos", None) => Arc::new(LanczosFilter::default()),
+        _ => usage_error("Unknown value for --filter!"),
+    };
+    let aperture_shape = match (aperture_mask, aperture_blades) {
+        (Some(mask), _) => ApertureShape::Mask(Arc::new(ApertureMask::new(&mask))),
+        (None, Some(blades)) => ApertureShape::Polygon { blades, rotation: aperture_rotation },
+        (None, None) => ApertureShape::Circle,
+    };
+    Options {
+        scene,
+        projection,
+        shutter: Shutter::rolling(curve, readout),
+        frames,
+        frames_per_second,
+        shutter_angle,
+        output_dir,
+        autofocus,
+        focal_length,
+        f_stop,
+        units_per_meter,
+        aperture_shape,
+        tilt,
+        swing,
+        shift,
+        filter,
+        sampler,
+        seed,
+        spectral,
+        image_width,
+        samples_per_pixel,
+        stats_json,
+        logger,
     }
 }
 
-fn render(image_width: u32, image_height: u32, samples_per_pixel: u32, max_depth: u32, world: Arc<dyn Hittable>, background: Color, camera: Arc<Camera>, tx: mpsc::Sender<PixelUpdate>) {
-    for j in (0..image_height).rev() {
-        for i in 0..image_width {
-            for _ in 0..samples_per_pixel {
-                let u = ((i as f64) + rand::random::<f64>()) / ((image_width - 1) as f64);
-                let v = ((j as f64) + rand::random::<f64>()) / ((image_height - 1) as f64);
-                let ray = camera.get_ray(u, v);
+const TIME_START: f64 = 0.0;
+const TIME_END: f64 = 1.0;
+// Image circle of the fisheye projections, in degrees.
+const FISHEYE_FOV: f64 = 180.0;
 
-                tx.send(PixelUpdate { color: ray_color(&ray, &background, world.as_ref(), max_depth), x: i as usize, y: j as usize}).unwrap();
-            }
+fn build_camera(options: &Options, scene: &Scene, settings: &RenderSettings, key: &CameraKeyframe, time_start: f64, time_end: f64) -> Arc<dyn Camera> {
+    let mut key = key.clone();
+    let lookfrom = key.lookfrom.clone();
+    let lookat = key.lookat.clone();
+    let vup = scene.vup.clone();
+    let shutter = options.shutter.clone();
+    // Of the image actually rendered, so pixels stay square after rounding its height.
+    let aspect_ratio = settings.image_width as f64 / settings.image_height as f64;
+    // Only the perspective camera has a lens, and only it and the orthographic camera take a field of view.
+    let has_lens_options = options.f_stop.is_some() || options.autofocus.is_some() || !matches!(options.aperture_shape, ApertureShape::Circle)
+        || options.tilt != 0.0 || options.swing != 0.0 || options.shift != (0.0, 0.0);
+    match options.projection {
+        Projection::Perspective => (),
+        _ if has_lens_options => usage_error("--f-stop, --autofocus, --aperture-*, --tilt, --swing and --shift need --camera perspective!"),
+        Projection::Orthographic => (),
+        _ if options.focal_length.is_some() => usage_error("--focal-length needs --camera perspective or orthographic!"),
+        _ => (),
+    }
+    if options.focal_length.is_some() || options.f_stop.is_some() {
+        let f_stop = options.f_stop.unwrap_or(f64::INFINITY);
+        let lens = match options.focal_length {
+            Some(focal_length) => Lens::new(focal_length, f_stop),
+            None => Lens::from_vfov(key.vfov, f_stop),
+        };
+        key.vfov = lens.vfov();
+        if options.f_stop.is_some() {
+            key.aperture = lens.aperture(options.units_per_meter);
         }
     }
+    if let Some((x, y)) = options.autofocus {
+        let s = x / (settings.image_width - 1) as f64;
+        let t = 1.0 - y / (settings.image_height - 1) as f64;
+        match autofocus(scene.world.as_ref(), &lookfrom, &lookat, &vup, key.vfov, aspect_ratio, s, t, time_start) {
+            Some(focus_dist) => key.focus_dist = focus_dist,
+            None => log::warn("camera", "Autofocus ray hit nothing, keeping the focus distance", &[
+                ("x", x.to_string()),
+                ("y", y.to_string()),
+                ("focus_dist", key.focus_dist.to_string()),
+            ]),
+        }
+    }
+    match options.projection {
+        Projection::Perspective => Arc::new(PerspectiveCamera::new(lookfrom, lookat, vup, key.vfov, aspect_ratio, key.aperture, key.focus_dist, time_start, time_end)
+            .with_shutter(shutter)
+            .with_aperture_shape(options.aperture_shape.clone())
+            .with_tilt_shift(options.tilt, options.swing, options.shift.0, options.shift.1)),
+        Projection::Orthographic => {
+            // Frame the same region a perspective camera would see at `lookat`.
+            let viewport_height = 2.0 * (&lookfrom - &lookat).length() * (util::degrees_to_radians(key.vfov) / 2.0).tan();
+            Arc::new(OrthographicCamera::new(lookfrom, lookat, vup, viewport_height, aspect_ratio, time_start, time_end).with_shutter(shutter))
+        },
+        Projection::FisheyeEquidistant => Arc::new(FisheyeCamera::new(lookfrom, lookat, vup, FISHEYE_FOV, aspect_ratio, FisheyeMapping::Equidistant, time_start, time_end).with_shutter(shutter)),
+        Projection::FisheyeEquisolid => Arc::new(FisheyeCamera::new(lookfrom, lookat, vup, FISHEYE_FOV, aspect_ratio, FisheyeMapping::Equisolid, time_start, time_end).with_shutter(shutter)),
+        Projection::Equirectangular => Arc::new(EquirectangularCamera::new(lookfrom, lookat, vup, time_start, time_end).with_shutter(shutter)),
+    }
+}
+
+fn create_file(path: &Path) -> BufWriter<File> {
+    BufWriter::new(File::create(path).unwrap_or_else(|err| panic!("Could not create {}: {}", path.display(), err)))
 }
 
 fn main() {
-    // Image
-    const ASPECT_RATIO: f64 = 16.0 / 9.0;
-    //const ASPECT_RATIO: f64 = 1.0;
-    const IMAGE_WIDTH: u32 = 600;
-    const IMAGE_HEIGHT: u32 = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as u32;
-    const SAMPLES_PER_PIXEL: u32 = 30;
-    const MAX_DEPTH: u32 = 50;
-    const THREAD_COUNT: u32 = 8;
-    const TIME_START: f64 = 0.0;
-    const TIME_END: f64 = 1.0;
-    // World
-    let (world, lookfrom, lookat, vfov, aperture, background) = get_scene(std::env::args().nth(1).unwrap_or("0".to_string()).trim().parse().unwrap_or(0));
+    let options = parse_options();
+    // Before loading the scene, so warnings from that go through it too.
+    set_logger(options.logger.clone());
+    let timing = FrameTiming { frames_per_second: options.frames_per_second, shutter_angle: options.shutter_angle };
+    // Moving objects are bounded over every time that gets rendered.
+    let (time_start, time_end) = match options.frames {
+        None => (TIME_START, TIME_END),
+        Some(frames) => timing.sequence_window(frames),
+    };
+    let scene = get_scene(options.scene, options.seed, time_start, time_end);
 
-    // Camera
-    let vup = Vec3 { x: 0.0, y: 1.0, z: 0.0 };
-    let dist_to_focus = 10.0;
-    let cam = Arc::new(Camera::new(lookfrom, lookat, vup, vfov, ASPECT_RATIO, aperture, dist_to_focus, TIME_START, TIME_END));
-    // Render
-    let mut final_image = Image::new(IMAGE_WIDTH as usize, IMAGE_HEIGHT as usize);
-    let (tx, rx) = mpsc::channel::<PixelUpdate>();
-    for _ in 0..THREAD_COUNT {
-        let sender = tx.clone();
-        let world_ref = world.clone();
-        let camera_ref = cam.clone();
-        let background_clone = background.clone();
-        thread::spawn( || {
-            render(IMAGE_WIDTH, IMAGE_HEIGHT, SAMPLES_PER_PIXEL / THREAD_COUNT, MAX_DEPTH, world_ref, background_clone, camera_ref, sender);
-        });
-    }
-    let expected_updates: u64 = (SAMPLES_PER_PIXEL / THREAD_COUNT) as u64 * THREAD_COUNT as u64 * IMAGE_HEIGHT as u64 * IMAGE_WIDTH as u64;
-    let print_frequency: u64 = (SAMPLES_PER_PIXEL / THREAD_COUNT) as u64 * THREAD_COUNT as u64 * IMAGE_WIDTH as u64;
-    let mut update_count: u64 = 0;
-    loop {
-        if let Ok(update) = rx.try_recv() {
-            update_count += 1;
-            final_image.add_sample(update.x, update.y, update.color);
-            if update_count % print_frequency == 0 {
-                eprint!("\rCurrent completion: {:.2}%", (update_count as f64 / expected_updates as f64) * 100.0)
+    let image_width = options.image_width.unwrap_or(scene.settings.image_width);
+    let image_height = RenderSettings::image_height_for(image_width, options.projection.aspect_ratio(scene.aspect_ratio()));
+    let settings = RenderSettings {
+        image_width,
+        image_height,
+        samples_per_pixel: options.samples_per_pixel.unwrap_or(scene.settings.samples_per_pixel),
+        sampler: options.sampler,
+        seed: options.seed,
+        filter: options.filter.clone(),
+        spectral: options.spectral,
+        ..scene.settings.clone()
+    };
+
+    match options.frames {
+        None => {
+            let key = scene.camera.at(TIME_START);
+            let cam = build_camera(&options, &scene, &settings, &key, TIME_START, TIME_END);
+            let (final_image, stats) = render_with_progress(&scene, cam, &settings, &mut ConsoleProgress::new());
+            final_image.write(&mut std::io::stdout());
+            if let Some(path) = &options.stats_json {
+                writeln!(create_file(path), "{}", stats.to_json()).unwrap_or_else(|err| panic!("Could not write {}: {}", path.display(), err));
             }
-        } else {
-            if Arc::strong_count(&world) == 1 {
-                break
+            eprintln!("Done.");
+        },
+        Some(frames) => {
+            let mut stats_file = options.stats_json.as_deref().map(create_file);
+            for frame in 0..frames {
+                let (time_start, time_end) = timing.shutter_window(frame);
+                // The camera holds still within a frame; blur comes from objects moving during the shutter window.
+                let key = scene.camera.at((time_start + time_end) / 2.0);
+                let cam = build_camera(&options, &scene, &settings, &key, time_start, time_end);
+                // A different seed per frame, so noise doesn't stay fixed on the screen while the picture moves.
+                let frame_settings = RenderSettings { seed: options.seed.wrapping_add(frame as u64), ..settings.clone() };
+                let (image, stats) = render_with_progress(&scene, cam, &frame_settings, &mut ConsoleProgress::new());
+                let path = options.output_dir.join(FrameTiming::file_name(frame));
+                image.write_png(&mut create_file(&path)).unwrap_or_else(|err| panic!("Could not write {}: {}", path.display(), err));
+                if let Some(file) = &mut stats_file {
+                    // Flushed every frame, so the stats can be followed while the sequence renders.
+                    writeln!(file, "{{\"frame\":{},\"stats\":{}}}", frame + 1, stats.to_json()).and_then(|_| file.flush())
+                        .unwrap_or_else(|err| panic!("Could not write {}: {}", options.stats_json.as_ref().unwrap().display(), err));
+                }
+                eprintln!("Wrote {} ({}/{}).", path.display(), frame + 1, frames);
             }
-        }
+            eprintln!("Done.");
+        },
     }
-    final_image.write(&mut std::io::stdout());
-    eprintln!("\nDone.");
 }
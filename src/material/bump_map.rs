@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use super::Material;
+use crate::hittable::HitRecord;
+use crate::texture::Texture;
+use crate::vec3::Color;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+
+// Step in u and v for the finite differences of the height.
+const DELTA: f64 = 1e-3;
+
+/// Gives `material` the shading of a surface raised by `height`, read with `Texture::scalar` and times `scale` in
+/// world units, without moving it: only the normal changes.
+pub struct BumpMap {
+    pub material: Arc<dyn Material>,
+    pub height: Arc<dyn Texture>,
+    pub scale: f64,
+}
+
+impl BumpMap {
+    fn height_at(&self, hit_record: &HitRecord, du: f64, dv: f64) -> f64 {
+        let p = &hit_record.p + du * &hit_record.tangent + dv * &hit_record.bitangent;
+        self.scale * self.height.scalar(hit_record.u + du, hit_record.v + dv, &p)
+    }
+
+    fn bump(&self, hit_record: &HitRecord) -> HitRecord {
+        let outward_normal = if hit_record.front_face { hit_record.normal.clone() } else { -&hit_record.normal };
+        let height = self.height_at(hit_record, 0.0, 0.0);
+        let (height_du, height_dv) = ((self.height_at(hit_record, DELTA, 0.0) - height) / DELTA, (self.height_at(hit_record, 0.0, DELTA) - height) / DELTA);
+        // The tangents of the raised surface, whose normal keeps to the same side as the one it is raised off.
+        let tangent = &hit_record.tangent + height_du * &outward_normal;
+        let bitangent = &hit_record.bitangent + height_dv * &outward_normal;
+        let mut normal = tangent.cross(&bitangent);
+        if normal.near_zero() {
+            return hit_record.clone();
+        }
+        if hit_record.tangent.cross(&hit_record.bitangent).dot(&outward_normal) < 0.0 {
+            normal = -normal;
+        }
+        let normal = normal.unit_vector();
+        HitRecord { normal: if hit_record.front_face { normal } else { -normal }, tangent, bitangent, ..hit_record.clone() }
+    }
+}
+
+impl Material for BumpMap {
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        self.material.emitted(hit_record)
+    }
+
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        self.material.scatter(ray_in, &self.bump(hit_record), attenuation, scattered, sampler)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Metal;
+    use crate::material::test_util::{hit_record, ray_towards_origin, scatter_many, vec3, white};
+    use crate::vec3::Point3;
+
+    // Rises by one per unit of u.
+    struct Ramp;
+
+    impl Texture for Ramp {
+        fn value(&self, u: f64, _: f64, _: &Point3) -> Color {
+            vec3(u, u, u)
+        }
+    }
+
+    fn bumped_mirror(scale: f64) -> BumpMap {
+        BumpMap { material: Arc::new(Metal::new(white(), 0.0)), height: Arc::new(Ramp), scale }
+    }
+
+    #[test]
+    fn slope_tilts_the_normal() {
+        let mut hit_record = hit_record(true);
+        hit_record.tangent = vec3(2.0, 0.0, 0.0);
+        hit_record.bitangent = vec3(0.0, 1.0, 0.0);
+        // Rising by 2 over the 2 units of one u: a 45 degree slope up towards +x, so straight down reflects to -x.
+        let (_, direction, _) = scatter_many(&bumped_mirror(2.0), &ray_towards_origin(&vec3(0.0, 0.0, 1.0)), &hit_record, 1).remove(0);
+        assert!((&direction - &vec3(-1.0, 0.0, 0.0)).length() < 1e-6, "Reflected to ({})", direction);
+    }
+
+    #[test]
+    fn slope_tilts_the_back_face_the_same_way() {
+        // The same surface, with its outward normal -z, seen from behind.
+        let mut hit_record = hit_record(false);
+        hit_record.tangent = vec3(2.0, 0.0, 0.0);
+        hit_record.bitangent = vec3(0.0, -1.0, 0.0);
+        let (_, direction, _) = scatter_many(&bumped_mirror(2.0), &ray_towards_origin(&vec3(0.0, 0.0, 1.0)), &hit_record, 1).remove(0);
+        assert!((&direction - &vec3(1.0, 0.0, 0.0)).length() < 1e-6, "Reflected to ({})", direction);
+    }
+
+    #[test]
+    fn flat_without_tangents() {
+        let (_, direction, _) = scatter_many(&bumped_mirror(2.0), &ray_towards_origin(&vec3(0.0, 0.6, 0.8)), &hit_record(true), 1).remove(0);
+        assert!((&direction - &vec3(0.0, -0.6, 0.8)).length() < 1e-12, "Reflected to ({})", direction);
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use super::Material;
+use super::frame::Frame;
+use super::fresnel::fresnel_dielectric;
+use super::microfacet::TrowbridgeReitz;
+use crate::hittable::HitRecord;
+use crate::texture::{SolidColor, Texture};
+use crate::vec3::{Color, Point3};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+
+/// Any material under a thin, clear dielectric coat, like varnished wood or glazed ceramic. The coat reflects as
+/// its GGX microfacets and exact Fresnel dictate, and everything else reaches `base`.
+// TODO: The coat doesn't bend light on its way to the base, nor reflect any of it back down on its way out.
+pub struct Coated {
+    pub base: Arc<dyn Material>,
+    /// Read with `Texture::scalar`.
+    pub index_of_refraction: Arc<dyn Texture>,
+    /// Roughness of the coat along `HitRecord::tangent` and across it, from 0 to 1. Read with `Texture::scalar`.
+    pub roughness_x: Arc<dyn Texture>,
+    pub roughness_y: Arc<dyn Texture>,
+}
+
+impl Coated {
+    /// `roughness` of the coat goes from 0, a glossy varnish, to 1.
+    pub fn new(base: Arc<dyn Material>, index_of_refraction: f64, roughness: f64) -> Self {
+        let roughness: Arc<dyn Texture> = Arc::new(SolidColor::from_value(roughness));
+        Self { base, index_of_refraction: Arc::new(SolidColor::from_value(index_of_refraction)), roughness_x: roughness.clone(), roughness_y: roughness }
+    }
+
+    fn distribution(&self, u: f64, v: f64, p: &Point3) -> TrowbridgeReitz {
+        let alpha = |roughness: &Arc<dyn Texture>| TrowbridgeReitz::roughness_to_alpha(roughness.scalar(u, v, p));
+        TrowbridgeReitz { alpha_x: alpha(&self.roughness_x), alpha_y: alpha(&self.roughness_y) }
+    }
+}
+
+impl Material for Coated {
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        self.base.emitted(hit_record)
+    }
+
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        let u = sampler.get_2d();
+        let u_fresnel = sampler.get_1d();
+        // Only the outside is coated.
+        if !hit_record.front_face {
+            return self.base.scatter(ray_in, hit_record, attenuation, scattered, sampler);
+        }
+
+        let frame = Frame::from_z_and_tangent(&hit_record.normal, &hit_record.tangent);
+        let wo = frame.to_local(&-ray_in.direction.unit_vector());
+        if wo.z <= 0.0 {
+            return false;
+        }
+
+        let (u_hit, v_hit, p) = (hit_record.u, hit_record.v, &hit_record.p);
+        let distribution = self.distribution(u_hit, v_hit, p);
+        let wm = distribution.sample_normal(&wo, u);
+        if u_fresnel >= fresnel_dielectric(wo.dot(&wm), 1.0 / self.index_of_refraction.scalar(u_hit, v_hit, p)) {
+            return self.base.scatter(ray_in, hit_record, attenuation, scattered, sampler);
+        }
+        let wi = (-&wo).reflect(&wm);
+        let weight = distribution.masking_weight(&wo, &wi);
+        *attenuation = Color { x: weight, y: weight, z: weight };
+        *scattered = Ray { origin: hit_record.p.clone(), direction: frame.to_world(&wi), time: ray_in.time, wavelengths: ray_in.wavelengths };
+        wi.z > 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::material::test_util::{hit_record, ray_towards_origin, sampler, scatter_many, vec3, white, white_furnace};
+
+    const VARNISH: f64 = 1.5;
+
+    #[test]
+    fn smooth_coat_mirrors_by_its_fresnel_reflectance() {
+        let count = 100_000;
+        let incoming = vec3(0.0, 0.6, 0.8);
+        let varnished = Coated::new(Arc::new(Lambertian::from_color(white())), VARNISH, 0.0);
+        let samples = scatter_many(&varnished, &ray_towards_origin(&incoming), &hit_record(true), count);
+        let mirrored = samples.iter().filter(|(_, direction, _)| (direction - &vec3(0.0, -0.6, 0.8)).length() < 1e-9).count() as f64 / count as f64;
+        let expected = fresnel_dielectric(0.8, 1.0 / VARNISH);
+        let tolerance = 5.0 * (expected * (1.0 - expected) / count as f64).sqrt();
+        assert!((mirrored - expected).abs() < tolerance, "Mirrored {} instead of {}", mirrored, expected);
+    }
+
+    #[test]
+    fn back_faces_use_up_the_same_dimensions() {
+        let base = Arc::new(Lambertian::from_color(white()));
+        let varnished = Coated::new(base.clone(), VARNISH, 0.5);
+        let (ray, hit_record) = (ray_towards_origin(&vec3(0.0, 0.6, 0.8)), hit_record(false));
+        let (mut attenuation, mut coated, mut uncoated) = (Color::new(), Ray::new(), Ray::new());
+        varnished.scatter(&ray, &hit_record, &mut attenuation, &mut coated, &mut sampler());
+        // The base gets the dimensions after the coat's.
+        let mut skipped = sampler();
+        skipped.get_2d();
+        skipped.get_1d();
+        base.scatter(&ray, &hit_record, &mut attenuation, &mut uncoated, &mut skipped);
+        assert_eq!((coated.direction.x, coated.direction.y, coated.direction.z), (uncoated.direction.x, uncoated.direction.y, uncoated.direction.z));
+    }
+
+    #[test]
+    fn white_furnace_loses_little_energy() {
+        let radiance = white_furnace(Arc::new(Coated::new(Arc::new(Lambertian::from_color(white())), VARNISH, 0.5)), 2000);
+        assert!(radiance > 0.8 && radiance <= 1.0, "White furnace gave {}", radiance);
+    }
+}
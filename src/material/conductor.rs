@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use super::Material;
+use super::frame::Frame;
+use super::fresnel::fresnel_conductor;
+use super::microfacet::TrowbridgeReitz;
+use crate::hittable::HitRecord;
+use crate::texture::{SolidColor, Texture};
+use crate::vec3::{Color, Point3};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+
+/// A metal with a rough surface of GGX microfacets, reflecting as its complex index of refraction `eta + i k`
+/// dictates. Unlike `Metal`, the reflectance and the way it changes towards grazing angles follow from the physics.
+pub struct Conductor {
+    pub eta: Arc<dyn Texture>,
+    pub k: Arc<dyn Texture>,
+    /// Roughness along `HitRecord::tangent` and across it, from 0 to 1. Read with `Texture::scalar`.
+    pub roughness_x: Arc<dyn Texture>,
+    pub roughness_y: Arc<dyn Texture>,
+}
+
+impl Conductor {
+    /// `roughness` goes from 0, a perfect mirror, to 1.
+    pub fn new(eta: Color, k: Color, roughness: f64) -> Self {
+        Self::anisotropic(eta, k, roughness, roughness)
+    }
+
+    pub fn anisotropic(eta: Color, k: Color, roughness_x: f64, roughness_y: f64) -> Self {
+        Self {
+            eta: Arc::new(SolidColor::from_color(eta)),
+            k: Arc::new(SolidColor::from_color(k)),
+            roughness_x: Arc::new(SolidColor::from_value(roughness_x)),
+            roughness_y: Arc::new(SolidColor::from_value(roughness_y)),
+        }
+    }
+
+    // Measured indices of refraction at the wavelengths of the red, green and blue channels, 650, 550 and 450 nm.
+
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(Color { x: 0.143, y: 0.374, z: 1.442 }, Color { x: 3.983, y: 2.385, z: 1.603 }, roughness)
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(Color { x: 0.200, y: 0.924, z: 1.102 }, Color { x: 3.912, y: 2.452, z: 2.142 }, roughness)
+    }
+
+    pub fn aluminium(roughness: f64) -> Self {
+        Self::new(Color { x: 1.657, y: 0.880, z: 0.521 }, Color { x: 9.224, y: 6.270, z: 4.837 }, roughness)
+    }
+
+    pub fn silver(roughness: f64) -> Self {
+        Self::new(Color { x: 0.155, y: 0.117, z: 0.138 }, Color { x: 4.828, y: 3.122, z: 2.147 }, roughness)
+    }
+
+    fn distribution(&self, u: f64, v: f64, p: &Point3) -> TrowbridgeReitz {
+        let alpha = |roughness: &Arc<dyn Texture>| TrowbridgeReitz::roughness_to_alpha(roughness.scalar(u, v, p));
+        TrowbridgeReitz { alpha_x: alpha(&self.roughness_x), alpha_y: alpha(&self.roughness_y) }
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        let frame = Frame::from_z_and_tangent(&hit_record.normal, &hit_record.tangent);
+        let wo = frame.to_local(&-ray_in.direction.unit_vector());
+        let u = sampler.get_2d();
+        if wo.z <= 0.0 {
+            return false;
+        }
+
+        let (u_hit, v_hit, p) = (hit_record.u, hit_record.v, &hit_record.p);
+        let distribution = self.distribution(u_hit, v_hit, p);
+        let wm = distribution.sample_normal(&wo, u);
+        let wi = (-&wo).reflect(&wm);
+        let (eta, k) = (self.eta.value(u_hit, v_hit, p), self.k.value(u_hit, v_hit, p));
+        *attenuation = fresnel_conductor(wo.dot(&wm), &eta, &k) * distribution.masking_weight(&wo, &wi);
+
+        *scattered = Ray { origin: hit_record.p.clone(), direction: frame.to_world(&wi), time: ray_in.time, wavelengths: ray_in.wavelengths };
+        wi.z > 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::material::test_util::{assert_matches_pdf, checker, checker_hits, hit_record, mirrors, ray_towards_origin, scatter_many, vec3, white, white_furnace};
+    use crate::vec3::Vec3;
+
+    // The value of a constant parameter.
+    fn constant(texture: &Arc<dyn Texture>) -> Color {
+        texture.value(0.0, 0.0, &Point3::new())
+    }
+
+    #[test]
+    fn reflectance_at_normal_incidence_matches_the_closed_form() {
+        let gold = Conductor::gold(0.0);
+        let (eta, k) = (constant(&gold.eta), constant(&gold.k));
+        let reflectance = fresnel_conductor(1.0, &eta, &k);
+        for ((eta, k), reflectance) in eta.into_iter().zip(&k).zip(&reflectance) {
+            let expected = ((eta - 1.0).powi(2) + k * k) / ((eta + 1.0).powi(2) + k * k);
+            assert!((reflectance - expected).abs() < 1e-9, "Reflectance {} instead of {}", reflectance, expected);
+        }
+    }
+
+    #[test]
+    fn reflectance_goes_to_one_at_grazing_angles() {
+        for metal in [Conductor::gold(0.0), Conductor::copper(0.0), Conductor::aluminium(0.0), Conductor::silver(0.0)] {
+            let reflectance = fresnel_conductor(1e-6, &constant(&metal.eta), &constant(&metal.k));
+            assert!(reflectance.into_iter().all(|channel| (channel - 1.0).abs() < 1e-4), "Grazing reflectance ({})", reflectance);
+        }
+    }
+
+    #[test]
+    fn smooth_reflects_about_the_normal_with_fresnel_attenuation() {
+        let incoming = vec3(0.6, -0.3, 0.8).unit_vector();
+        let gold = Conductor::gold(0.0);
+        let (attenuation, direction, scattered) = scatter_many(&gold, &ray_towards_origin(&incoming), &hit_record(true), 1).remove(0);
+        assert!(scattered);
+        assert!((&direction - &vec3(-incoming.x, -incoming.y, incoming.z)).length() < 1e-12, "Reflected to ({})", direction);
+        assert!((&attenuation - &fresnel_conductor(incoming.z, &constant(&gold.eta), &constant(&gold.k))).length() < 1e-12);
+    }
+
+    #[test]
+    fn rough_reflections_are_distributed_like_visible_normals() {
+        let incoming = vec3(0.4, 0.3, 0.8).unit_vector();
+        let conductor = Conductor::anisotropic(white(), white(), 0.3f64.sqrt(), 0.6f64.sqrt());
+        let samples = scatter_many(&conductor, &ray_towards_origin(&incoming), &hit_record(true), 100_000);
+        // Reflections below the surface are absorbed, but they are still drawn from the same distribution.
+        let directions: Vec<Vec3> = samples.into_iter().map(|(_, direction, _)| direction).collect();
+        let distribution = conductor.distribution(0.0, 0.0, &Point3::new());
+        // The density of visible normals, times the Jacobian of reflecting about them.
+        assert_matches_pdf(&directions, |direction| {
+            let wm = (&incoming + direction).unit_vector();
+            distribution.g1(&incoming) * distribution.d(&wm) / (4.0 * incoming.z)
+        });
+    }
+
+    #[test]
+    fn anisotropy_follows_the_tangent() {
+        let mut hit_record = hit_record(true);
+        hit_record.tangent = vec3(0.0, 2.0, 0.0);
+        // Smooth along the tangent, rough across it.
+        let metal = Conductor::anisotropic(white(), vec3(1e6, 1e6, 1e6), 0.1, 0.7);
+        let samples = scatter_many(&metal, &ray_towards_origin(&vec3(0.0, 0.0, 1.0)), &hit_record, 10_000);
+        let spread = |axis: fn(&Vec3) -> f64| samples.iter().map(|(_, direction, _)| axis(direction).abs()).sum::<f64>();
+        assert!(spread(|direction| direction.y) < 0.2 * spread(|direction| direction.x));
+    }
+
+    #[test]
+    fn white_furnace_conserves_energy_when_smooth() {
+        // A huge extinction coefficient reflects (almost) everything.
+        let mirror = Conductor::new(white(), 1e6 * white(), 0.0);
+        let radiance = white_furnace(Arc::new(mirror), 2000);
+        assert!((radiance - 1.0).abs() < 1e-3, "White furnace gave {}", radiance);
+    }
+
+    #[test]
+    fn white_furnace_loses_little_energy_when_rough() {
+        // Single scattering misses the light that bounces between microfacets, which grows with roughness, but
+        // must never add any.
+        let rough = Conductor::new(white(), 1e6 * white(), 0.5);
+        let radiance = white_furnace(Arc::new(rough), 2000);
+        assert!(radiance > 0.8 && radiance <= 1.0, "White furnace gave {}", radiance);
+    }
+
+    #[test]
+    fn roughness_is_read_at_the_hit() {
+        // Smooth on the even squares, and rough on the odd ones.
+        let metal = Conductor { roughness_x: checker(0.8, 0.0), roughness_y: checker(0.8, 0.0), ..Conductor::new(white(), 1e6 * white(), 0.0) };
+        let (even, odd) = checker_hits();
+        assert!(mirrors(&metal, &even) && !mirrors(&metal, &odd));
+    }
+}
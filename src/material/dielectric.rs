@@ -1,39 +1,266 @@
-use super::Material;
-use crate::{hittable::HitRecord, vec3::Vec3};
+use std::sync::Arc;
+
+use super::{Dispersion, Fresnel, Material};
+use crate::hittable::HitRecord;
+use crate::texture::{SolidColor, Texture};
 use crate::vec3::Color;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 
+/// Glass, water and the like, with a perfectly smooth surface.
 pub struct Dielectric {
-    pub index_of_refraction: f64,
+    /// Read with `Texture::scalar`, and used for every wavelength unless there is `dispersion`.
+    pub index_of_refraction: Arc<dyn Texture>,
+    pub fresnel: Fresnel,
+    /// Fraction of light absorbed per unit distance travelled inside, per channel, read where the ray leaves. Zero
+    /// for clear glass.
+    pub absorption: Arc<dyn Texture>,
+    pub dispersion: Option<Dispersion>,
 }
 
 impl Dielectric {
-    fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
-        // Using Schlick's Approximation:
-        let mut r0 = (1.0 - ref_idx) / (1.0 + ref_idx);
-        r0 *= r0;
-        r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+    pub fn new(index_of_refraction: f64) -> Self {
+        Self { index_of_refraction: Arc::new(SolidColor::from_value(index_of_refraction)), fresnel: Fresnel::default(), absorption: Arc::new(SolidColor::from_color(Color::new())), dispersion: None }
+    }
+
+    pub fn with_fresnel(self, fresnel: Fresnel) -> Self {
+        Self { fresnel, ..self }
+    }
+
+    pub fn with_absorption(self, absorption: Color) -> Self {
+        Self { absorption: Arc::new(SolidColor::from_color(absorption)), ..self }
+    }
+
+    /// Makes the index of refraction depend on the wavelength. A path that hits this material gets narrowed down
+    /// to a single wavelength, so expect more color noise around it.
+    pub fn with_dispersion(self, dispersion: Dispersion) -> Self {
+        Self { dispersion: Some(dispersion), ..self }
     }
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        let u = sampler.get_1d();
+        let mut wavelengths = ray_in.wavelengths;
+        let mut index_of_refraction = self.index_of_refraction.scalar(hit_record.u, hit_record.v, &hit_record.p);
+        if let Some(dispersion) = &self.dispersion {
+            let lambda;
+            (wavelengths, lambda) = ray_in.wavelengths.narrow(sampler.get_1d());
+            index_of_refraction = dispersion.index_of_refraction(lambda);
+        }
         *attenuation = Color { x: 1.0, y: 1.0, z: 1.0 };
-        let refraction_ratio = if hit_record.front_face { 1.0 / self.index_of_refraction } else { self.index_of_refraction };
+        if !hit_record.front_face {
+            // On the way out, after travelling through the inside from where the ray started (Beer-Lambert).
+            let distance = hit_record.t * ray_in.direction.length();
+            let absorption = self.absorption.value(hit_record.u, hit_record.v, &hit_record.p);
+            *attenuation = &*attenuation * Color { x: (-absorption.x * distance).exp(), y: (-absorption.y * distance).exp(), z: (-absorption.z * distance).exp() };
+        }
+
+        let refraction_ratio = if hit_record.front_face { 1.0 / index_of_refraction } else { index_of_refraction };
         let unit_direction = ray_in.direction.unit_vector();
         let cos_theta = hit_record.normal.dot(&-&unit_direction).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let direction: Vec3;
-
-        if cannot_refract || Self::reflectance(cos_theta, refraction_ratio) > rand::random::<f64>() {
-            direction = unit_direction.reflect(&hit_record.normal)
+        let direction = if cannot_refract || self.fresnel.reflectance(cos_theta, refraction_ratio) > u {
+            unit_direction.reflect(&hit_record.normal)
         } else {
-            direction = unit_direction.refract(&hit_record.normal, refraction_ratio)
-        }
+            unit_direction.refract(&hit_record.normal, refraction_ratio)
+        };
 
-        *scattered = Ray { origin: hit_record.p.clone(), direction, time: ray_in.time };
+        *scattered = Ray { origin: hit_record.p.clone(), direction, time: ray_in.time, wavelengths };
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::material::fresnel::fresnel_dielectric;
+    use crate::material::test_util::{checker, checker_hits, hit_record, ray_towards_origin, sampler, scatter_many, vec3, white, white_furnace};
+    use crate::spectrum::{Wavelengths, LAMBDA_MAX, LAMBDA_MIN};
+    use crate::vec3::Vec3;
+
+    const GLASS: f64 = 1.5;
+    const SAMPLES: usize = 100_000;
+
+    fn glass() -> Dielectric {
+        Dielectric::new(GLASS)
+    }
+
+    // Sine of the angle between `direction` and the z axis, the normal in every test.
+    fn sin_theta(direction: &Vec3) -> f64 {
+        let direction = direction.unit_vector();
+        (direction.x * direction.x + direction.y * direction.y).sqrt()
+    }
+
+    #[test]
+    fn reflects_with_fresnel_probability() {
+        for degrees in [0.0, 30.0, 60.0, 80.0_f64] {
+            let incoming = vec3(degrees.to_radians().sin(), 0.0, degrees.to_radians().cos());
+            let samples = scatter_many(&glass(), &ray_towards_origin(&incoming), &hit_record(true), SAMPLES);
+            let reflected = samples.iter().filter(|(_, direction, _)| direction.z > 0.0).count() as f64 / SAMPLES as f64;
+            // Schlick's approximation, which is what the material implements by default.
+            let r0 = ((GLASS - 1.0) / (GLASS + 1.0)).powi(2);
+            let expected = r0 + (1.0 - r0) * (1.0 - incoming.z).powi(5);
+            // Five standard deviations of the binomial estimate.
+            let tolerance = 5.0 * (expected * (1.0 - expected) / SAMPLES as f64).sqrt();
+            assert!((reflected - expected).abs() < tolerance, "Reflected {} at {} degrees instead of {}", reflected, degrees, expected);
+        }
+    }
+
+    #[test]
+    fn reflects_with_exact_fresnel_probability() {
+        let glass = glass().with_fresnel(Fresnel::Exact);
+        for degrees in [0.0, 30.0, 60.0, 80.0_f64] {
+            let incoming = vec3(degrees.to_radians().sin(), 0.0, degrees.to_radians().cos());
+            let samples = scatter_many(&glass, &ray_towards_origin(&incoming), &hit_record(true), SAMPLES);
+            let reflected = samples.iter().filter(|(_, direction, _)| direction.z > 0.0).count() as f64 / SAMPLES as f64;
+            let expected = fresnel_dielectric(incoming.z, 1.0 / GLASS);
+            let tolerance = 5.0 * (expected * (1.0 - expected) / SAMPLES as f64).sqrt();
+            assert!((reflected - expected).abs() < tolerance, "Reflected {} at {} degrees instead of {}", reflected, degrees, expected);
+        }
+    }
+
+    #[test]
+    fn index_of_refraction_is_read_at_the_hit() {
+        // Diamond on the even squares, and glass on the odd ones.
+        let material = Dielectric { index_of_refraction: checker(GLASS, 2.4), ..glass() };
+        let incoming = vec3(0.6, 0.0, 0.8);
+        let (even, odd) = checker_hits();
+        for (hit_record, eta) in [(even, 2.4), (odd, GLASS)] {
+            let samples = scatter_many(&material, &ray_towards_origin(&incoming), &hit_record, 100);
+            for (_, direction, _) in samples.iter().filter(|(_, direction, _)| direction.z < 0.0) {
+                assert!((sin_theta(&incoming) - eta * sin_theta(direction)).abs() < 1e-9, "Refracted to ({})", direction);
+            }
+        }
+    }
+
+    #[test]
+    fn refraction_obeys_snells_law() {
+        for (front_face, eta_in, eta_out) in [(true, 1.0, GLASS), (false, GLASS, 1.0)] {
+            // Stay below the critical angle when leaving the glass.
+            for degrees in [0.0, 20.0, 40.0_f64] {
+                let incoming = vec3(degrees.to_radians().sin() * 0.6, degrees.to_radians().sin() * 0.8, degrees.to_radians().cos());
+                let samples = scatter_many(&glass(), &ray_towards_origin(&incoming), &hit_record(front_face), 1000);
+                let refracted: Vec<&Vec3> = samples.iter().map(|(_, direction, _)| direction).filter(|direction| direction.z < 0.0).collect();
+                assert!(!refracted.is_empty());
+                for direction in refracted {
+                    assert!((eta_in * sin_theta(&incoming) - eta_out * sin_theta(direction)).abs() < 1e-9, "Refracted to ({}) from ({})", direction, incoming);
+                    // In the plane of incidence, carrying on along the surface the way the ray was travelling.
+                    assert!(incoming.cross(direction).z.abs() < 1e-9 && incoming.x * direction.x <= 0.0, "Refracted out of the plane of incidence to ({})", direction);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn refraction_is_reversible() {
+        let incoming = vec3(0.3, -0.4, 0.8).unit_vector();
+        let samples = scatter_many(&glass(), &ray_towards_origin(&incoming), &hit_record(true), 100);
+        let (_, refracted, _) = samples.into_iter().find(|(_, direction, _)| direction.z < 0.0).unwrap();
+        // Send the ray back the way it came, from inside the glass.
+        let mut back_record = hit_record(false);
+        back_record.normal = vec3(0.0, 0.0, -1.0);
+        let samples = scatter_many(&glass(), &ray_towards_origin(&refracted), &back_record, 100);
+        let (_, back, _) = samples.into_iter().find(|(_, direction, _)| direction.z > 0.0).unwrap();
+        assert!((back.unit_vector() - &incoming).length() < 1e-9, "Refracted back to ({}) instead of ({})", back, incoming);
+    }
+
+    #[test]
+    fn total_internal_reflection_beyond_the_critical_angle() {
+        let critical = (1.0 / GLASS).asin();
+        for angle in [critical + 0.01, critical + 0.3, 1.5] {
+            let incoming = vec3(angle.sin(), 0.0, angle.cos());
+            for (_, direction, scattered) in scatter_many(&glass(), &ray_towards_origin(&incoming), &hit_record(false), 1000) {
+                assert!(scattered);
+                assert!((&direction - &vec3(-incoming.x, 0.0, incoming.z)).length() < 1e-9, "Escaped to ({}) past the critical angle", direction);
+            }
+        }
+    }
+
+    #[test]
+    fn white_furnace_conserves_energy() {
+        let radiance = white_furnace(Arc::new(glass()), 2000);
+        assert!((radiance - 1.0).abs() < 1e-3, "White furnace gave {}", radiance);
+    }
+
+    #[test]
+    fn absorbs_over_the_distance_travelled_inside() {
+        let absorption = vec3(0.1, 0.5, 2.0);
+        let glass = glass().with_absorption(absorption.clone());
+        let incoming = vec3(0.0, 0.6, 0.8);
+        // The ray from `ray_towards_origin` has a unit direction, so this is also the distance.
+        let distance = 3.0;
+        let (mut inside, mut outside) = (hit_record(false), hit_record(true));
+        inside.t = distance;
+        outside.t = distance;
+        for (attenuation, _, _) in scatter_many(&glass, &ray_towards_origin(&incoming), &inside, 100) {
+            for (attenuation, absorption) in attenuation.into_iter().zip(&absorption) {
+                assert!((attenuation - (-absorption * distance).exp()).abs() < 1e-12, "Attenuated by {} instead of {}", attenuation, (-absorption * distance).exp());
+            }
+        }
+        // Nothing is absorbed on the way in.
+        for (attenuation, _, _) in scatter_many(&glass, &ray_towards_origin(&incoming), &outside, 100) {
+            assert_eq!((attenuation.x, attenuation.y, attenuation.z), (white().x, white().y, white().z));
+        }
+    }
+
+    #[test]
+    fn dispersion_picks_a_wavelength_and_refracts_by_it() {
+        let glass = glass().with_dispersion(Dispersion::diamond());
+        let incoming = vec3(0.6, 0.0, 0.8);
+        let mut sampler = sampler();
+        for _ in 0..1000 {
+            let (mut attenuation, mut scattered) = (Color::new(), Ray::new());
+            assert!(glass.scatter(&ray_towards_origin(&incoming), &hit_record(true), &mut attenuation, &mut scattered, &mut sampler));
+            let Wavelengths::Single(lambda) = scattered.wavelengths else {
+                panic!("Dispersion left the path with {:?}", scattered.wavelengths);
+            };
+            assert!((LAMBDA_MIN..LAMBDA_MAX).contains(&lambda));
+            // The path gets weighted for its wavelength by `ray_color`, not here.
+            assert_eq!((attenuation.x, attenuation.y, attenuation.z), (white().x, white().y, white().z));
+            if scattered.direction.z < 0.0 {
+                let eta = Dispersion::diamond().index_of_refraction(lambda);
+                assert!((sin_theta(&incoming) - eta * sin_theta(&scattered.direction)).abs() < 1e-9, "Refracted to ({}) at {} nm", scattered.direction, lambda);
+            }
+        }
+    }
+
+    #[test]
+    fn dispersion_keeps_the_wavelengths_it_is_given() {
+        let glass = glass().with_dispersion(Dispersion::bk7());
+        let incoming = vec3(0.6, 0.0, 0.8);
+        let spectral = Wavelengths::Spectral { lambda: [450.0, 550.0, 650.0], hero_only: false };
+        // A spectral path is narrowed down to its hero wavelength, a narrowed one stays as it is.
+        for (wavelengths, narrowed) in [(Wavelengths::Single(450.0), Wavelengths::Single(450.0)), (spectral, Wavelengths::Spectral { lambda: [450.0, 550.0, 650.0], hero_only: true })] {
+            let ray_in = Ray { wavelengths, ..ray_towards_origin(&incoming) };
+            let mut sampler = sampler();
+            for _ in 0..100 {
+                let (mut attenuation, mut scattered) = (Color::new(), Ray::new());
+                glass.scatter(&ray_in, &hit_record(true), &mut attenuation, &mut scattered, &mut sampler);
+                assert_eq!(scattered.wavelengths, narrowed);
+                if scattered.direction.z < 0.0 {
+                    let eta = Dispersion::bk7().index_of_refraction(450.0);
+                    assert!((sin_theta(&incoming) - eta * sin_theta(&scattered.direction)).abs() < 1e-9, "Refracted to ({})", scattered.direction);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn white_furnace_conserves_energy_on_average_with_dispersion() {
+        // Each path turns the color of its wavelength, but together they are white.
+        let radiance = white_furnace(Arc::new(glass().with_dispersion(Dispersion::diamond())), 4000);
+        assert!((radiance - 1.0).abs() < 0.03, "White furnace gave {}", radiance);
+    }
+
+    #[test]
+    fn does_not_tint() {
+        for (attenuation, _, _) in scatter_many(&glass(), &ray_towards_origin(&vec3(0.0, 0.6, 0.8)), &hit_record(true), 100) {
+            assert_eq!((attenuation.x, attenuation.y, attenuation.z), (white().x, white().y, white().z));
+        }
+    }
+}
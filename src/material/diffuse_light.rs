@@ -1,29 +1,101 @@
+use std::f64::consts::PI;
 use std::sync::Arc;
 
 use super::Material;
-use crate::{hittable::HitRecord, texture::Texture, vec3::Point3};
+use crate::{hittable::HitRecord, texture::Texture};
 use crate::vec3::Color;
 use crate::texture::SolidColor;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::spectrum;
+
+/// Which sides of a surface a `DiffuseLight` shines from. The front is the side its outward normal points to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sides {
+    #[default]
+    Both,
+    Front,
+    Back,
+}
 
 pub struct DiffuseLight {
-    emit: Arc<dyn Texture>,
+    /// Emitted radiance, the same in every direction: nits, if the scene is in metres and lit photometrically.
+    pub emit: Arc<dyn Texture>,
+    pub sides: Sides,
 }
 
 impl DiffuseLight {
+    pub fn new(emit: Arc<dyn Texture>) -> Self {
+        Self { emit, sides: Sides::default() }
+    }
+
     pub fn from_color(color: Color) -> Self {
-        Self {
-            emit: Arc::new(SolidColor::from_color(color)),
-        }
+        Self::new(Arc::new(SolidColor::from_color(color)))
+    }
+
+    /// A light of `color`, whatever its brightness, that gives off `power` from each side that shines when it covers
+    /// `area`. The power is luminous, weighted by how bright each channel looks, in units of luminance times area
+    /// rather than watts.
+    pub fn from_power(color: Color, power: f64, area: f64) -> Self {
+        let luminance = spectrum::luminance(&color);
+        assert!(luminance > 0.0, "Lights set by their power need a color with some brightness!");
+        let radiance = power / (PI * area) / luminance;
+        Self::from_color(radiance * color)
+    }
+
+    /// A light the color of a black body at `temperature` kelvin, with a luminance of `radiance`.
+    pub fn blackbody(temperature: f64, radiance: f64) -> Self {
+        Self::from_color(radiance * spectrum::blackbody(temperature))
+    }
+
+    pub fn with_sides(self, sides: Sides) -> Self {
+        Self { sides, ..self }
     }
 }
 
 impl Material for DiffuseLight {
-    fn scatter(&self, _: &Ray, _: &HitRecord, _: &mut Color, _: &mut Ray) -> bool {
+    fn scatter(&self, _: &Ray, _: &HitRecord, _: &mut Color, _: &mut Ray, _: &mut dyn Sampler) -> bool {
         false
     }
 
-    fn emitted(&self, u: f64, v: f64, point: &Point3) -> Color {
-        self.emit.value(u, v, point)
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        let shines = match self.sides {
+            Sides::Both => true,
+            Sides::Front => hit_record.front_face,
+            Sides::Back => !hit_record.front_face,
+        };
+        if !shines {
+            return Color { x: 0.0, y: 0.0, z: 0.0 };
+        }
+        self.emit.value(hit_record.u, hit_record.v, &hit_record.p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::test_util::{hit_record, vec3, white};
+
+    #[test]
+    fn shines_from_the_chosen_sides() {
+        for (sides, front, back) in [(Sides::Both, true, true), (Sides::Front, true, false), (Sides::Back, false, true)] {
+            let light = DiffuseLight::from_color(white()).with_sides(sides);
+            assert_eq!(light.emitted(&hit_record(true)).x > 0.0, front, "{:?} from the front", sides);
+            assert_eq!(light.emitted(&hit_record(false)).x > 0.0, back, "{:?} from the back", sides);
+        }
+    }
+
+    #[test]
+    fn power_spreads_over_the_area_and_hemisphere() {
+        // Radiance L over a hemisphere gives off pi L per unit area.
+        let emitted = DiffuseLight::from_power(vec3(2.0, 2.0, 2.0), 100.0, 4.0).emitted(&hit_record(true));
+        let expected = 100.0 / (4.0 * PI);
+        assert!((&emitted - &vec3(expected, expected, expected)).length() < 1e-6 * expected, "Emitted ({})", emitted);
+    }
+
+    #[test]
+    #[should_panic(expected = "some brightness")]
+    fn power_needs_a_bright_color() {
+        DiffuseLight::from_power(vec3(0.0, 0.0, 0.0), 100.0, 4.0);
     }
 }
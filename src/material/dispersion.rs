@@ -0,0 +1,57 @@
+/// How the index of refraction of a dielectric changes with wavelength, which splits white light into its colors.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Dispersion {
+    /// Cauchy's equation, `n = a + b / lambda^2` with `lambda` in micrometres.
+    Cauchy { a: f64, b: f64 },
+    /// The Sellmeier equation, `n^2 = 1 + sum(b[i] lambda^2 / (lambda^2 - c[i]))` with `lambda` in micrometres, which
+    /// is how glass makers publish their data.
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// Schott N-BK7, the most common optical glass.
+    pub fn bk7() -> Self {
+        Dispersion::Sellmeier { b: [1.03961212, 0.231792344, 1.01046945], c: [0.00600069867, 0.0200179144, 103.560653] }
+    }
+
+    pub fn fused_silica() -> Self {
+        Dispersion::Sellmeier { b: [0.6961663, 0.4079426, 0.8974794], c: [0.0684043_f64.powi(2), 0.1162414_f64.powi(2), 9.896161_f64.powi(2)] }
+    }
+
+    /// Disperses much more than glass, hence the fire of a cut diamond.
+    pub fn diamond() -> Self {
+        Dispersion::Sellmeier { b: [0.3306, 4.3356, 0.0], c: [0.1750_f64.powi(2), 0.1060_f64.powi(2), 0.0] }
+    }
+
+    /// The index of refraction at `wavelength` nanometres.
+    pub fn index_of_refraction(&self, wavelength: f64) -> f64 {
+        let lambda2 = (wavelength / 1000.0).powi(2);
+        match self {
+            Dispersion::Cauchy { a, b } => a + b / lambda2,
+            Dispersion::Sellmeier { b, c } => (1.0 + (0..3).map(|i| b[i] * lambda2 / (lambda2 - c[i])).sum::<f64>()).sqrt(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The helium d line, where catalogues quote the index of refraction.
+    const D_LINE: f64 = 587.6;
+
+    #[test]
+    fn matches_catalogue_indices() {
+        for (dispersion, expected) in [(Dispersion::bk7(), 1.5168), (Dispersion::fused_silica(), 1.4585), (Dispersion::diamond(), 2.4175)] {
+            let n = dispersion.index_of_refraction(D_LINE);
+            assert!((n - expected).abs() < 1e-3, "Index of refraction {} instead of {}", n, expected);
+        }
+    }
+
+    #[test]
+    fn blue_bends_more_than_red() {
+        for dispersion in [Dispersion::Cauchy { a: 1.5, b: 0.004 }, Dispersion::bk7(), Dispersion::fused_silica(), Dispersion::diamond()] {
+            assert!(dispersion.index_of_refraction(450.0) > dispersion.index_of_refraction(650.0), "{:?} disperses the wrong way", dispersion);
+        }
+    }
+}
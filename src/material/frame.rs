@@ -0,0 +1,41 @@
+use crate::vec3::Vec3;
+
+/// An orthonormal basis with `z` along the shading normal, for working with directions in local coordinates.
+pub(crate) struct Frame {
+    pub x: Vec3,
+    pub y: Vec3,
+    pub z: Vec3,
+}
+
+impl Frame {
+    /// Any frame around the unit vector `z`, continuous except where `z` crosses the xy plane (Duff et al. 2017).
+    pub fn from_z(z: &Vec3) -> Self {
+        let sign = 1.0_f64.copysign(z.z);
+        let a = -1.0 / (sign + z.z);
+        let b = z.x * z.y * a;
+        Self {
+            x: Vec3 { x: 1.0 + sign * z.x * z.x * a, y: sign * b, z: -sign * z.x },
+            y: Vec3 { x: b, y: sign + z.y * z.y * a, z: -z.y },
+            z: z.clone(),
+        }
+    }
+
+    /// The frame around the unit vector `z` with `x` as close to `tangent` as it gets, or any frame if `tangent` is
+    /// zero or along `z`.
+    pub fn from_z_and_tangent(z: &Vec3, tangent: &Vec3) -> Self {
+        let x = tangent - z.dot(tangent) * z;
+        if x.length_squared() <= 1e-12 * tangent.length_squared() {
+            return Self::from_z(z);
+        }
+        let x = x.unit_vector();
+        Self { y: z.cross(&x), x, z: z.clone() }
+    }
+
+    pub fn to_local(&self, v: &Vec3) -> Vec3 {
+        Vec3 { x: v.dot(&self.x), y: v.dot(&self.y), z: v.dot(&self.z) }
+    }
+
+    pub fn to_world(&self, v: &Vec3) -> Vec3 {
+        v.x * &self.x + v.y * &self.y + v.z * &self.z
+    }
+}
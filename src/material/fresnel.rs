@@ -0,0 +1,102 @@
+use crate::vec3::Color;
+
+/// How a dielectric works out the fraction of light it reflects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fresnel {
+    /// Schlick's approximation: cheap, and close enough for most glass.
+    #[default]
+    Schlick,
+    /// The Fresnel equations for unpolarized light, which also get total internal reflection right on their own.
+    Exact,
+}
+
+impl Fresnel {
+    /// Fraction of light reflected at `cos_theta_i` off an interface with `etai_over_etat`, the index of refraction
+    /// the light comes from over the one it goes into.
+    pub fn reflectance(self, cos_theta_i: f64, etai_over_etat: f64) -> f64 {
+        match self {
+            Fresnel::Schlick => schlick(cos_theta_i, etai_over_etat),
+            Fresnel::Exact => fresnel_dielectric(cos_theta_i, etai_over_etat),
+        }
+    }
+}
+
+fn schlick(cosine: f64, ref_idx: f64) -> f64 {
+    let mut r0 = (1.0 - ref_idx) / (1.0 + ref_idx);
+    r0 *= r0;
+    r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+}
+
+pub(crate) fn fresnel_dielectric(cos_theta_i: f64, etai_over_etat: f64) -> f64 {
+    let cos_theta_i = cos_theta_i.clamp(0.0, 1.0);
+    let sin2_theta_t = etai_over_etat * etai_over_etat * (1.0 - cos_theta_i * cos_theta_i);
+    if sin2_theta_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+    let r_s = (etai_over_etat * cos_theta_i - cos_theta_t) / (etai_over_etat * cos_theta_i + cos_theta_t);
+    let r_p = (cos_theta_i - etai_over_etat * cos_theta_t) / (cos_theta_i + etai_over_etat * cos_theta_t);
+    (r_s * r_s + r_p * r_p) / 2.0
+}
+
+/// Fraction of light a conductor with complex index of refraction `eta + i k` reflects at `cos_theta_i`, per channel.
+pub(crate) fn fresnel_conductor(cos_theta_i: f64, eta: &Color, k: &Color) -> Color {
+    let channel = |eta: f64, k: f64| {
+        let cos2 = cos_theta_i.clamp(0.0, 1.0).powi(2);
+        let sin2 = 1.0 - cos2;
+        let t0 = eta * eta - k * k - sin2;
+        let a2_plus_b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+        let t1 = a2_plus_b2 + cos2;
+        let t2 = 2.0 * cos2.sqrt() * a;
+        let r_s = (t1 - t2) / (t1 + t2);
+        let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let r_p = r_s * (t3 - t4) / (t3 + t4);
+        (r_s + r_p) / 2.0
+    };
+    Color { x: channel(eta.x, k.x), y: channel(eta.y, k.y), z: channel(eta.z, k.z) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLASS: f64 = 1.5;
+
+    #[test]
+    fn exact_matches_the_closed_form_at_normal_incidence() {
+        let expected = ((GLASS - 1.0) / (GLASS + 1.0)).powi(2);
+        for etai_over_etat in [1.0 / GLASS, GLASS] {
+            let reflectance = Fresnel::Exact.reflectance(1.0, etai_over_etat);
+            assert!((reflectance - expected).abs() < 1e-12, "Reflectance {} instead of {}", reflectance, expected);
+        }
+    }
+
+    #[test]
+    fn exact_reflects_only_s_polarized_light_at_brewsters_angle() {
+        let cos_theta = GLASS.atan().cos();
+        // Half the light is s-polarized, and reflects by ((n^2 - 1) / (n^2 + 1))^2 there.
+        let expected = ((GLASS * GLASS - 1.0) / (GLASS * GLASS + 1.0)).powi(2) / 2.0;
+        let reflectance = Fresnel::Exact.reflectance(cos_theta, 1.0 / GLASS);
+        assert!((reflectance - expected).abs() < 1e-12, "Reflectance {} instead of {}", reflectance, expected);
+    }
+
+    #[test]
+    fn exact_reflects_everything_beyond_the_critical_angle() {
+        let critical = (1.0 / GLASS).asin();
+        assert!(Fresnel::Exact.reflectance((critical - 0.01).cos(), GLASS) < 1.0);
+        for angle in [critical + 1e-6, critical + 0.3, 1.5] {
+            assert_eq!(Fresnel::Exact.reflectance(angle.cos(), GLASS), 1.0);
+        }
+    }
+
+    #[test]
+    fn schlick_stays_close_to_exact() {
+        for degrees in [0.0, 30.0, 60.0, 80.0_f64] {
+            let cos_theta = degrees.to_radians().cos();
+            let (schlick, exact) = (Fresnel::Schlick.reflectance(cos_theta, 1.0 / GLASS), Fresnel::Exact.reflectance(cos_theta, 1.0 / GLASS));
+            assert!((schlick - exact).abs() < 0.03, "Schlick {} and exact {} at {} degrees", schlick, exact, degrees);
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::{hittable::HitRecord, texture::Texture, vec3::Vec3};
 use crate::vec3::Color;
 use crate::texture::SolidColor;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 
 pub struct Isotropic {
     albedo: Arc<dyn Texture>,
@@ -25,9 +26,43 @@ impl Isotropic {
 }
 
 impl Material for Isotropic {
-    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
-        *scattered = Ray { origin: hit_record.p.clone(), direction: Vec3::random_in_unit_sphere(), time: ray_in.time };
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        *scattered = Ray { origin: hit_record.p.clone(), direction: Vec3::sample_unit_vector(sampler.get_2d()), time: ray_in.time, wavelengths: ray_in.wavelengths };
         *attenuation = self.albedo.value(hit_record.u, hit_record.v, &hit_record.p);
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::hittable::{ConstantMedium, HittableList, Sphere};
+    use crate::spectrum::Wavelengths;
+    use crate::material::test_util::{assert_matches_pdf, hit_record, ray_towards_origin, sampler, scatter_many, vec3, white};
+    use crate::texture::SolidColor;
+
+    #[test]
+    fn scatters_uniformly_over_the_sphere() {
+        let samples = scatter_many(&Isotropic::from_color(white()), &ray_towards_origin(&vec3(0.3, 0.1, 1.0)), &hit_record(true), 100_000);
+        let directions: Vec<Vec3> = samples.into_iter().map(|(_, direction, _)| direction).collect();
+        assert_matches_pdf(&directions, |_| 1.0 / (4.0 * PI));
+    }
+
+    #[test]
+    fn white_medium_conserves_energy() {
+        // Surfaces with an isotropic material can trap paths inside closed objects, so test it the way it is
+        // used, inside a medium.
+        let boundary = Arc::new(Sphere { center: vec3(0.0, 0.0, 0.0), radius: 1.0, material: Arc::new(Isotropic::from_color(white())) });
+        let mut world = HittableList::new();
+        world.add(Arc::new(ConstantMedium::new(boundary, 2.0, Arc::new(SolidColor::from_color(white())))));
+        let mut sampler = sampler();
+        for i in 0..1000 {
+            let offset = i as f64 / 1000.0 - 0.5;
+            let ray = Ray { origin: vec3(offset, -offset, -5.0), direction: vec3(0.0, 0.0, 1.0), time: 0.0, wavelengths: Wavelengths::Rgb };
+            let color = crate::render::ray_color(&ray, &white(), &world, 1000, &mut sampler);
+            assert_eq!((color.x, color.y, color.z), (1.0, 1.0, 1.0));
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::{hittable::HitRecord, texture::Texture, vec3::Vec3};
 use crate::vec3::Color;
 use crate::texture::SolidColor;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 
 pub struct Lambertian {
     pub albedo: Arc<dyn Texture>,
@@ -19,16 +20,51 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, ray_in : &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
-        let mut scatter_direction = &hit_record.normal + Vec3::random_unit_vector();
+    fn scatter(&self, ray_in : &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        let mut scatter_direction = &hit_record.normal + Vec3::sample_unit_vector(sampler.get_2d());
 
         // Catch zero-vector scatter directions that will generate issues later
         if scatter_direction.near_zero() {
             scatter_direction = hit_record.normal.clone();
         }
 
-        *scattered = Ray { origin: hit_record.p.clone(), direction: scatter_direction, time: ray_in.time };
+        *scattered = Ray { origin: hit_record.p.clone(), direction: scatter_direction, time: ray_in.time, wavelengths: ray_in.wavelengths };
         *attenuation = self.albedo.value(hit_record.u, hit_record.v, &hit_record.p);
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::material::test_util::{assert_matches_pdf, hit_record, ray_towards_origin, scatter_many, vec3, white, white_furnace};
+
+    const SAMPLES: usize = 100_000;
+
+    #[test]
+    fn scatters_with_cosine_distribution() {
+        // The distribution must not depend on where the light came from.
+        for incoming in [vec3(0.0, 0.0, 1.0), vec3(1.0, 0.0, 0.2), vec3(-0.3, 0.8, 0.5)] {
+            let samples = scatter_many(&Lambertian::from_color(white()), &ray_towards_origin(&incoming), &hit_record(true), SAMPLES);
+            assert!(samples.iter().all(|(_, _, scattered)| *scattered));
+            let directions: Vec<Vec3> = samples.into_iter().map(|(_, direction, _)| direction).collect();
+            assert_matches_pdf(&directions, |direction| direction.z.max(0.0) / PI);
+        }
+    }
+
+    #[test]
+    fn attenuates_by_albedo() {
+        let albedo = vec3(0.2, 0.5, 0.9);
+        for (attenuation, _, _) in scatter_many(&Lambertian::from_color(albedo.clone()), &ray_towards_origin(&vec3(0.0, 0.0, 1.0)), &hit_record(true), 100) {
+            assert_eq!((attenuation.x, attenuation.y, attenuation.z), (albedo.x, albedo.y, albedo.z));
+        }
+    }
+
+    #[test]
+    fn white_furnace_conserves_energy() {
+        let radiance = white_furnace(Arc::new(Lambertian::from_color(white())), 2000);
+        assert!((radiance - 1.0).abs() < 1e-3, "White furnace gave {}", radiance);
+    }
+}
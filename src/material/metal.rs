@@ -1,18 +1,110 @@
+use std::sync::Arc;
+
 use super::Material;
-use crate::{hittable::HitRecord, vec3::Vec3};
+use crate::{hittable::HitRecord, texture::Texture, vec3::Vec3};
 use crate::vec3::Color;
+use crate::texture::SolidColor;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 
 pub struct Metal {
-    pub albedo: Color,
-    pub fuzz: f64, // TODO: This should have value 0.0 - 1.0; this is not enforced
+    pub albedo: Arc<dyn Texture>,
+    /// Read with `Texture::scalar`.
+    pub fuzz: Arc<dyn Texture>, // TODO: This should have value 0.0 - 1.0; this is not enforced
+}
+
+impl Metal {
+    pub fn new(albedo: Color, fuzz: f64) -> Self {
+        Self {
+            albedo: Arc::new(SolidColor::from_color(albedo)),
+            fuzz: Arc::new(SolidColor::from_value(fuzz)),
+        }
+    }
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        let (u, v, p) = (hit_record.u, hit_record.v, &hit_record.p);
+        let fuzz = Vec3::sample_in_unit_sphere(sampler.get_2d(), sampler.get_1d());
         let reflected = ray_in.direction.unit_vector().reflect(&hit_record.normal);
-        *scattered = Ray { origin: hit_record.p.clone(), direction: reflected + self.fuzz * Vec3::random_in_unit_sphere(), time: ray_in.time };
-        *attenuation = self.albedo.clone();
+        *scattered = Ray { origin: hit_record.p.clone(), direction: reflected + self.fuzz.scalar(u, v, p) * fuzz, time: ray_in.time, wavelengths: ray_in.wavelengths };
+        *attenuation = self.albedo.value(u, v, p);
         scattered.direction.dot(&hit_record.normal) > 0.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::material::test_util::{assert_matches_pdf, checker, checker_colors, checker_hits, hit_record, mirrors, ray_towards_origin, scatter_many, vec3, white, white_furnace};
+
+    fn mirror() -> Metal {
+        Metal::new(white(), 0.0)
+    }
+
+    #[test]
+    fn mirror_reflects_about_the_normal() {
+        let incoming = vec3(0.6, -0.3, 0.8).unit_vector();
+        for (_, direction, scattered) in scatter_many(&mirror(), &ray_towards_origin(&incoming), &hit_record(true), 100) {
+            assert!(scattered);
+            assert!((&direction - &vec3(-incoming.x, -incoming.y, incoming.z)).length() < 1e-12, "Reflected to ({})", direction);
+        }
+    }
+
+    #[test]
+    fn mirror_is_reversible() {
+        let incoming = vec3(-0.2, 0.5, 0.4).unit_vector();
+        let (_, outgoing, _) = scatter_many(&mirror(), &ray_towards_origin(&incoming), &hit_record(true), 1).remove(0);
+        let (_, back, _) = scatter_many(&mirror(), &ray_towards_origin(&outgoing), &hit_record(true), 1).remove(0);
+        assert!((&back - &incoming).length() < 1e-12, "Reflected back to ({}) instead of ({})", back, incoming);
+    }
+
+    #[test]
+    fn fuzz_is_distributed_like_a_ball_around_the_reflection() {
+        let fuzz = 0.4;
+        let incoming = vec3(0.5, 0.0, 1.0).unit_vector();
+        let reflected = vec3(-incoming.x, -incoming.y, incoming.z);
+        let metal = Metal::new(white(), fuzz);
+        let samples = scatter_many(&metal, &ray_towards_origin(&incoming), &hit_record(true), 100_000);
+        // Directions that end up below the surface are absorbed, but they are still drawn from the same ball.
+        let directions: Vec<Vec3> = samples.into_iter().map(|(_, direction, _)| direction).collect();
+        // Density of the direction of a point uniform in the ball of radius `fuzz` around `reflected`: the volume
+        // of the ball along the ray through the origin, r^2 dr, over the volume of the ball.
+        assert_matches_pdf(&directions, |direction| {
+            let along = direction.dot(&reflected);
+            let discriminant = along * along - (1.0 - fuzz * fuzz);
+            if discriminant < 0.0 {
+                return 0.0;
+            }
+            let (near, far) = ((along - discriminant.sqrt()).max(0.0), along + discriminant.sqrt());
+            (far.powi(3) - near.powi(3)) / (4.0 * PI * fuzz.powi(3))
+        });
+    }
+
+    #[test]
+    fn parameters_are_read_at_the_hit() {
+        // Polished gold on the even squares, and rough and dull on the odd ones.
+        let gold = vec3(1.0, 0.8, 0.3);
+        let metal = Metal { albedo: checker_colors(vec3(0.2, 0.2, 0.2), gold.clone()), fuzz: checker(0.5, 0.0) };
+        let (even, odd) = checker_hits();
+        assert!(mirrors(&metal, &even) && !mirrors(&metal, &odd));
+        let albedo = scatter_many(&metal, &ray_towards_origin(&vec3(0.0, 0.6, 0.8)), &even, 1).remove(0).0;
+        assert!((&albedo - &gold).length() < 1e-12, "Albedo ({})", albedo);
+    }
+
+    #[test]
+    fn white_furnace_conserves_energy_without_fuzz() {
+        let radiance = white_furnace(Arc::new(mirror()), 2000);
+        assert!((radiance - 1.0).abs() < 1e-3, "White furnace gave {}", radiance);
+    }
+
+    #[test]
+    fn white_furnace_loses_energy_with_fuzz() {
+        // Fuzz that points below the surface is absorbed, so rough metal darkens but must never brighten.
+        let radiance = white_furnace(Arc::new(Metal::new(white(), 0.8)), 2000);
+        assert!(radiance > 0.5 && radiance <= 1.0, "White furnace gave {}", radiance);
+    }
+}
@@ -0,0 +1,86 @@
+use std::f64::consts::PI;
+
+use crate::vec3::Vec3;
+
+// Below this width the distribution is treated as a perfect mirror, since sampling it would be numerically unstable.
+const SMOOTH_ALPHA: f64 = 1e-3;
+
+/// The Trowbridge-Reitz (GGX) distribution of microfacet normals, in the local frame of the surface (normal
+/// along +z), with widths `alpha_x` and `alpha_y` along the x and y axes.
+pub(crate) struct TrowbridgeReitz {
+    pub alpha_x: f64,
+    pub alpha_y: f64,
+}
+
+impl TrowbridgeReitz {
+    /// The usual perceptual mapping: widths grow with the square of the roughness, which looks more linear.
+    pub fn roughness_to_alpha(roughness: f64) -> f64 {
+        roughness.clamp(0.0, 1.0).powi(2)
+    }
+
+    pub fn is_smooth(&self) -> bool {
+        self.alpha_x.max(self.alpha_y) < SMOOTH_ALPHA
+    }
+
+    /// Density of microfacet normals `wm`, over projected area.
+    // Scattering only needs to sample the distribution, so only the tests evaluate it.
+    #[cfg(test)]
+    pub fn d(&self, wm: &Vec3) -> f64 {
+        if wm.z <= 0.0 {
+            return 0.0;
+        }
+        let e = (wm.x / self.alpha_x).powi(2) + (wm.y / self.alpha_y).powi(2) + wm.z * wm.z;
+        1.0 / (PI * self.alpha_x * self.alpha_y * e * e)
+    }
+
+    fn lambda(&self, w: &Vec3) -> f64 {
+        if w.z == 0.0 {
+            return f64::INFINITY;
+        }
+        let tan2_alpha2 = ((self.alpha_x * w.x).powi(2) + (self.alpha_y * w.y).powi(2)) / (w.z * w.z);
+        ((1.0 + tan2_alpha2).sqrt() - 1.0) / 2.0
+    }
+
+    /// Fraction of microfacets visible from `w` (Smith).
+    pub fn g1(&self, w: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// Fraction of microfacets visible from both `wo` and `wi`, with their heights correlated.
+    pub fn g(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// A microfacet normal for scattering `wo` off: sampled by `sample_visible_normal`, or the surface normal itself
+    /// when smooth.
+    pub fn sample_normal(&self, wo: &Vec3, u: (f64, f64)) -> Vec3 {
+        if self.is_smooth() { Vec3 { x: 0.0, y: 0.0, z: 1.0 } } else { self.sample_visible_normal(wo, u) }
+    }
+
+    /// What light scattered from `wo` to `wi` off a normal from `sample_normal` is weighted by: the BRDF or BTDF
+    /// times the cosine over the density of sampling, apart from the Fresnel term, which cancels out to this.
+    pub fn masking_weight(&self, wo: &Vec3, wi: &Vec3) -> f64 {
+        if self.is_smooth() { 1.0 } else { self.g(wo, wi) / self.g1(wo) }
+    }
+
+    /// Samples a microfacet normal as seen from `wo`, which must be above the surface, in proportion to its visible
+    /// area (Heitz 2018).
+    pub fn sample_visible_normal(&self, wo: &Vec3, u: (f64, f64)) -> Vec3 {
+        // Stretch to the configuration where the distribution is the hemisphere.
+        let wh = Vec3 { x: self.alpha_x * wo.x, y: self.alpha_y * wo.y, z: wo.z }.unit_vector();
+        let length_squared = wh.x * wh.x + wh.y * wh.y;
+        let t1 = if length_squared > 0.0 { Vec3 { x: -wh.y, y: wh.x, z: 0.0 } / length_squared.sqrt() } else { Vec3 { x: 1.0, y: 0.0, z: 0.0 } };
+        let t2 = wh.cross(&t1);
+
+        // A point on the projected hemisphere: a disk, with the half hidden behind the hemisphere squashed.
+        let r = u.0.sqrt();
+        let phi = 2.0 * PI * u.1;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + wh.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let nh = p1 * &t1 + p2 * &t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * &wh;
+
+        // And back.
+        Vec3 { x: self.alpha_x * nh.x, y: self.alpha_y * nh.y, z: nh.z.max(1e-6) }.unit_vector()
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use super::Material;
+use crate::hittable::HitRecord;
+use crate::texture::{SolidColor, Texture};
+use crate::vec3::Color;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+
+/// A blend of two materials, say rust over paint: each scatter picks one of them at random, `second` with
+/// probability `weight`, read with `Texture::scalar` so it can vary over the surface like a dirt mask.
+pub struct MixMaterial {
+    pub first: Arc<dyn Material>,
+    pub second: Arc<dyn Material>,
+    pub weight: Arc<dyn Texture>,
+}
+
+impl MixMaterial {
+    /// The same blend everywhere.
+    pub fn new(first: Arc<dyn Material>, second: Arc<dyn Material>, weight: f64) -> Self {
+        Self { first, second, weight: Arc::new(SolidColor::from_value(weight)) }
+    }
+}
+
+impl Material for MixMaterial {
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        let weight = self.weight.scalar(hit_record.u, hit_record.v, &hit_record.p).clamp(0.0, 1.0);
+        (1.0 - weight) * self.first.emitted(hit_record) + weight * self.second.emitted(hit_record)
+    }
+
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        let weight = self.weight.scalar(hit_record.u, hit_record.v, &hit_record.p);
+        let material = if sampler.get_1d() < weight { &self.second } else { &self.first };
+        material.scatter(ray_in, hit_record, attenuation, scattered, sampler)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{DiffuseLight, Lambertian, Metal};
+    use crate::material::test_util::{checker, checker_hits, hit_record, ray_towards_origin, scatter_many, vec3, white};
+
+    const SAMPLES: usize = 100_000;
+
+    fn mirror() -> Arc<dyn Material> {
+        Arc::new(Metal::new(white(), 0.0))
+    }
+
+    fn mirrored_fraction(material: &dyn Material, hit_record: &HitRecord) -> f64 {
+        let incoming = vec3(0.0, 0.6, 0.8);
+        let samples = scatter_many(material, &ray_towards_origin(&incoming), hit_record, SAMPLES);
+        samples.iter().filter(|(_, direction, _)| (direction - &vec3(0.0, -0.6, 0.8)).length() < 1e-9).count() as f64 / SAMPLES as f64
+    }
+
+    #[test]
+    fn picks_the_second_material_by_weight() {
+        let blend = MixMaterial::new(Arc::new(Lambertian::from_color(white())), mirror(), 0.3);
+        let mirrored = mirrored_fraction(&blend, &hit_record(true));
+        let tolerance = 5.0 * (0.3 * 0.7 / SAMPLES as f64).sqrt();
+        assert!((mirrored - 0.3).abs() < tolerance, "Mirrored {} instead of 0.3", mirrored);
+    }
+
+    #[test]
+    fn weight_is_read_at_the_hit() {
+        // All mirror on the even squares, and all diffuse on the odd ones.
+        let blend = MixMaterial { first: Arc::new(Lambertian::from_color(white())), second: mirror(), weight: checker(0.0, 1.0) };
+        let (even, odd) = checker_hits();
+        assert_eq!((mirrored_fraction(&blend, &even), mirrored_fraction(&blend, &odd)), (1.0, 0.0));
+    }
+
+    #[test]
+    fn blends_emission() {
+        let blend = MixMaterial::new(Arc::new(DiffuseLight::from_color(vec3(4.0, 2.0, 0.0))), mirror(), 0.25);
+        let emitted = blend.emitted(&hit_record(true));
+        assert!((&emitted - &vec3(3.0, 1.5, 0.0)).length() < 1e-12, "Emitted ({})", emitted);
+    }
+}
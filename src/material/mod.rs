@@ -2,20 +2,47 @@ mod lambertian;
 pub use lambertian::Lambertian;
 mod metal;
 pub use metal::Metal;
+mod conductor;
+pub use conductor::Conductor;
 mod dielectric;
 pub use dielectric::Dielectric;
+mod rough_dielectric;
+pub use rough_dielectric::RoughDielectric;
+mod thin_dielectric;
+pub use thin_dielectric::ThinDielectric;
+mod fresnel;
+pub use fresnel::Fresnel;
+mod dispersion;
+pub use dispersion::Dispersion;
 mod diffuse_light;
-pub use diffuse_light::DiffuseLight;
+pub use diffuse_light::{DiffuseLight, Sides};
+mod principled;
+pub use principled::Principled;
+mod mix_material;
+pub use mix_material::MixMaterial;
+mod coated;
+pub use coated::Coated;
+mod bump_map;
+pub use bump_map::BumpMap;
+mod normal_map;
+pub use normal_map::NormalMap;
 mod isotropic;
 pub use isotropic::Isotropic;
 
-use crate::{hittable::HitRecord, vec3::Point3};
+mod frame;
+mod microfacet;
+
+#[cfg(test)]
+mod test_util;
+
+use crate::hittable::HitRecord;
 use crate::vec3::Color;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 
 pub trait Material: Send + Sync {
-    fn emitted(&self, _: f64, _: f64, _: &Point3) -> Color {
+    fn emitted(&self, _: &HitRecord) -> Color {
         Color { x: 0.0, y: 0.0, z: 0.0 }    
     }
-    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray) -> bool;
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool;
 }
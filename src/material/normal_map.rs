@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use super::Material;
+use super::frame::Frame;
+use crate::hittable::HitRecord;
+use crate::texture::Texture;
+use crate::vec3::{Color, Vec3};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+
+/// Gives `material` the normals of a tangent-space normal map, the bluish images that come with most assets:
+/// red, green and blue from 0 to 1 stand for -1 to 1 along `HitRecord::tangent`, the bitangent and the outward
+/// normal.
+pub struct NormalMap {
+    pub material: Arc<dyn Material>,
+    pub normals: Arc<dyn Texture>,
+}
+
+impl NormalMap {
+    fn perturb(&self, hit_record: &HitRecord) -> HitRecord {
+        let outward_normal = if hit_record.front_face { hit_record.normal.clone() } else { -&hit_record.normal };
+        let mut frame = Frame::from_z_and_tangent(&outward_normal, &hit_record.tangent);
+        // Mirrored texture coordinates flip the bitangent.
+        if frame.y.dot(&hit_record.bitangent) < 0.0 {
+            frame.y = -frame.y;
+        }
+        let color = self.normals.value(hit_record.u, hit_record.v, &hit_record.p);
+        let normal = frame.to_world(&Vec3 { x: 2.0 * color.x - 1.0, y: 2.0 * color.y - 1.0, z: 2.0 * color.z - 1.0 });
+        if normal.near_zero() {
+            return hit_record.clone();
+        }
+        let normal = normal.unit_vector();
+        HitRecord { normal: if hit_record.front_face { normal } else { -normal }, ..hit_record.clone() }
+    }
+}
+
+impl Material for NormalMap {
+    fn emitted(&self, hit_record: &HitRecord) -> Color {
+        self.material.emitted(hit_record)
+    }
+
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        self.material.scatter(ray_in, &self.perturb(hit_record), attenuation, scattered, sampler)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Metal;
+    use crate::material::test_util::{hit_record, ray_towards_origin, scatter_many, vec3, white};
+    use crate::texture::SolidColor;
+
+    fn mapped_mirror(color: Color) -> NormalMap {
+        NormalMap { material: Arc::new(Metal::new(white(), 0.0)), normals: Arc::new(SolidColor::from_color(color)) }
+    }
+
+    #[test]
+    fn flat_normal_map_changes_nothing() {
+        let mut hit_record = hit_record(true);
+        hit_record.tangent = vec3(1.0, 1.0, 0.0);
+        hit_record.bitangent = vec3(-1.0, 1.0, 0.0);
+        let incoming = vec3(0.3, 0.0, 0.8).unit_vector();
+        let (_, direction, _) = scatter_many(&mapped_mirror(vec3(0.5, 0.5, 1.0)), &ray_towards_origin(&incoming), &hit_record, 1).remove(0);
+        assert!((&direction - &vec3(-incoming.x, -incoming.y, incoming.z)).length() < 1e-12, "Reflected to ({})", direction);
+    }
+
+    #[test]
+    fn normals_follow_the_tangents() {
+        // Tilted 45 degrees towards the bitangent, which is -x here, so straight down reflects to -x. Seen from
+        // behind, the outward normal tilts the same way, which turns the facing normal the other way.
+        let tilted = vec3(0.5, 0.5 + 0.5 / 2.0_f64.sqrt(), 0.5 + 0.5 / 2.0_f64.sqrt());
+        for (front_face, reflected) in [(true, vec3(-1.0, 0.0, 0.0)), (false, vec3(1.0, 0.0, 0.0))] {
+            let mut hit_record = hit_record(front_face);
+            hit_record.tangent = vec3(0.0, 1.0, 0.0);
+            hit_record.bitangent = vec3(-1.0, 0.0, 0.0);
+            let (_, direction, _) = scatter_many(&mapped_mirror(tilted.clone()), &ray_towards_origin(&vec3(0.0, 0.0, 1.0)), &hit_record, 1).remove(0);
+            assert!((&direction - &reflected).length() < 1e-9, "Reflected to ({}) with front face {}", direction, front_face);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use super::Material;
+use super::frame::Frame;
+use super::fresnel::fresnel_dielectric;
+use super::microfacet::TrowbridgeReitz;
+use crate::hittable::HitRecord;
+use crate::texture::{SolidColor, Texture};
+use crate::vec3::{Color, Vec3};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+
+// The clear coat is a smooth varnish with an index of refraction of 1.5.
+const CLEARCOAT_ALPHA: f64 = 0.05;
+const CLEARCOAT_REFLECTANCE: f64 = 0.04;
+
+/// A principled material after Disney's and Blender's: one material whose few intuitive parameters cover plastic,
+/// metal, glass, cloth, skin and most things in between, so assets made for those tools map onto it. Every
+/// parameter is a texture, read with `Texture::scalar` except for the base color, and goes from 0 to 1.
+///
+/// The layers are chosen between at random, in proportion to how much light each one takes, and the ray follows
+/// that one alone.
+pub struct Principled {
+    pub base_color: Arc<dyn Texture>,
+    /// How much of the surface is metal, which reflects tinted by the base color, rather than dielectric.
+    pub metallic: Arc<dyn Texture>,
+    pub roughness: Arc<dyn Texture>,
+    /// Reflectance of the dielectric at normal incidence, scaled so 0.5 is the 4% of most materials.
+    pub specular: Arc<dyn Texture>,
+    /// A soft white glow at grazing angles, as off cloth.
+    pub sheen: Arc<dyn Texture>,
+    /// A glossy clear layer on top of everything else, like varnish or car paint.
+    pub clearcoat: Arc<dyn Texture>,
+    /// How much of the dielectric lets light through, like glass, rather than reflecting it diffusely.
+    pub transmission: Arc<dyn Texture>,
+    /// Flattens the diffuse reflection like light scattered beneath the surface does in skin or wax. It is only an
+    /// approximation: the light still leaves where it arrived.
+    pub subsurface: Arc<dyn Texture>,
+    /// Of the transmissive part. Unlike the others, it isn't limited to 0 to 1.
+    pub index_of_refraction: Arc<dyn Texture>,
+}
+
+impl Principled {
+    /// A rough plastic in `base_color`.
+    pub fn new(base_color: Arc<dyn Texture>) -> Self {
+        let value = |value: f64| -> Arc<dyn Texture> { Arc::new(SolidColor::from_value(value)) };
+        Self {
+            base_color,
+            metallic: value(0.0),
+            roughness: value(0.5),
+            specular: value(0.5),
+            sheen: value(0.0),
+            clearcoat: value(0.0),
+            transmission: value(0.0),
+            subsurface: value(0.0),
+            index_of_refraction: value(1.5),
+        }
+    }
+
+    pub fn from_color(color: Color) -> Self {
+        Self::new(Arc::new(SolidColor::from_color(color)))
+    }
+}
+
+fn schlick(f0: &Color, cos_theta: f64) -> Color {
+    let white = Color { x: 1.0, y: 1.0, z: 1.0 };
+    f0 + (white - f0) * (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5)
+}
+
+fn gray(value: f64) -> Color {
+    Color { x: value, y: value, z: value }
+}
+
+/// The Disney diffuse reflectance, with retro-reflection that grows with `roughness`, blended towards Hanrahan and
+/// Krueger's approximation of subsurface scattering.
+fn diffuse(wo: &Vec3, wi: &Vec3, cos_d: f64, roughness: f64, subsurface: f64) -> f64 {
+    let (fo, fi) = ((1.0 - wo.z).powi(5), (1.0 - wi.z).powi(5));
+    let fd90 = 0.5 + 2.0 * roughness * cos_d * cos_d;
+    let retro_reflection = (1.0 + (fd90 - 1.0) * fo) * (1.0 + (fd90 - 1.0) * fi);
+    let fss90 = roughness * cos_d * cos_d;
+    let fss = (1.0 + (fss90 - 1.0) * fo) * (1.0 + (fss90 - 1.0) * fi);
+    let flattened = 1.25 * (fss * (1.0 / (wo.z + wi.z) - 0.5) + 0.5);
+    retro_reflection + (flattened - retro_reflection) * subsurface
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        let (u, v, p) = (hit_record.u, hit_record.v, &hit_record.p);
+        let frame = Frame::from_z(&hit_record.normal);
+        let wo = frame.to_local(&-ray_in.direction.unit_vector());
+        let (u_clearcoat, u_layer, u_fresnel) = (sampler.get_1d(), sampler.get_1d(), sampler.get_1d());
+        let u_normal = sampler.get_2d();
+        let u_diffuse = sampler.get_2d();
+        if wo.z <= 0.0 {
+            return false;
+        }
+
+        let base_color = self.base_color.value(u, v, p);
+        let roughness = self.roughness.scalar(u, v, p).clamp(0.0, 1.0);
+        let alpha = TrowbridgeReitz::roughness_to_alpha(roughness);
+        let distribution = TrowbridgeReitz { alpha_x: alpha, alpha_y: alpha };
+        let metallic = self.metallic.scalar(u, v, p);
+        let transmission = (1.0 - metallic) * self.transmission.scalar(u, v, p);
+
+        // Inside, only the transmissive part can have let the light in, and it is the only way out.
+        let (wi, refracted) = if !hit_record.front_face || u_layer >= 1.0 - transmission {
+            let index_of_refraction = self.index_of_refraction.scalar(u, v, p);
+            let refraction_ratio = if hit_record.front_face { 1.0 / index_of_refraction } else { index_of_refraction };
+            let wm = distribution.sample_normal(&wo, u_normal);
+            let refracted = u_fresnel >= fresnel_dielectric(wo.dot(&wm), refraction_ratio);
+            let wi = if refracted { (-&wo).refract(&wm, refraction_ratio) } else { (-&wo).reflect(&wm) };
+            // Tinted on the way in and out, so the base color is what a ray through a thin sheet ends up with.
+            let tint = if refracted { Color { x: base_color.x.sqrt(), y: base_color.y.sqrt(), z: base_color.z.sqrt() } } else { gray(1.0) };
+            *attenuation = tint * distribution.masking_weight(&wo, &wi);
+            (wi, refracted)
+        } else if u_clearcoat < self.clearcoat.scalar(u, v, p) * schlick(&gray(CLEARCOAT_REFLECTANCE), wo.z).x {
+            let coat = TrowbridgeReitz { alpha_x: CLEARCOAT_ALPHA, alpha_y: CLEARCOAT_ALPHA };
+            let wi = (-&wo).reflect(&coat.sample_normal(&wo, u_normal));
+            *attenuation = gray(coat.masking_weight(&wo, &wi));
+            (wi, false)
+        } else if u_layer < metallic {
+            let wm = distribution.sample_normal(&wo, u_normal);
+            let wi = (-&wo).reflect(&wm);
+            *attenuation = schlick(&base_color, wo.dot(&wm)) * distribution.masking_weight(&wo, &wi);
+            (wi, false)
+        } else {
+            let wm = distribution.sample_normal(&wo, u_normal);
+            let reflectance = 0.08 * self.specular.scalar(u, v, p);
+            if u_fresnel < schlick(&gray(reflectance), wo.dot(&wm)).x {
+                let wi = (-&wo).reflect(&wm);
+                *attenuation = gray(distribution.masking_weight(&wo, &wi));
+                (wi, false)
+            } else {
+                let mut wi = Vec3 { x: 0.0, y: 0.0, z: 1.0 } + Vec3::sample_unit_vector(u_diffuse);
+                if wi.near_zero() {
+                    wi = Vec3 { x: 0.0, y: 0.0, z: 1.0 };
+                }
+                let wi = wi.unit_vector();
+                let cos_d = (&wi + &wo).unit_vector().dot(&wi);
+                let sheen = self.sheen.scalar(u, v, p) * (1.0 - cos_d).powi(5);
+                *attenuation = base_color * diffuse(&wo, &wi, cos_d, roughness, self.subsurface.scalar(u, v, p)) + gray(sheen);
+                (wi, false)
+            }
+        };
+
+        *scattered = Ray { origin: hit_record.p.clone(), direction: frame.to_world(&wi), time: ray_in.time, wavelengths: ray_in.wavelengths };
+        // A microfacet can send light to the wrong side of the surface, where it is lost.
+        if refracted { wi.z < 0.0 } else { wi.z > 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::material::test_util::{assert_matches_pdf, checker, checker_hits, hit_record, mirrors, ray_towards_origin, scatter_many, vec3, white, white_furnace};
+
+    fn value(value: f64) -> Arc<dyn Texture> {
+        Arc::new(SolidColor::from_value(value))
+    }
+
+    #[test]
+    fn smooth_metal_mirrors_tinted_by_the_base_color() {
+        let incoming = vec3(0.0, 0.6, 0.8);
+        let gold = Principled { metallic: value(1.0), roughness: value(0.0), ..Principled::from_color(vec3(1.0, 0.8, 0.3)) };
+        for (attenuation, direction, scattered) in scatter_many(&gold, &ray_towards_origin(&incoming), &hit_record(true), 100) {
+            assert!(scattered);
+            assert!((&direction - &vec3(0.0, -0.6, 0.8)).length() < 1e-9, "Scattered to ({})", direction);
+            let expected = schlick(&vec3(1.0, 0.8, 0.3), 0.8);
+            assert!((&attenuation - &expected).length() < 1e-9, "Attenuation ({}) instead of ({})", attenuation, expected);
+        }
+    }
+
+    #[test]
+    fn diffuse_without_specular_is_distributed_by_cosine() {
+        let incoming = vec3(0.3, 0.0, 0.9).unit_vector();
+        let matte = Principled { roughness: value(0.0), specular: value(0.0), sheen: value(1.0), subsurface: value(0.5), ..Principled::from_color(white()) };
+        let samples = scatter_many(&matte, &ray_towards_origin(&incoming), &hit_record(true), 100_000);
+        let directions: Vec<Vec3> = samples.into_iter().map(|(_, direction, _)| direction.unit_vector()).collect();
+        assert_matches_pdf(&directions, |wi| wi.z.max(0.0) / PI);
+    }
+
+    #[test]
+    fn smooth_transmission_refracts_like_glass() {
+        let incoming = vec3(0.6, 0.0, 0.8);
+        let glass = Principled { roughness: value(0.0), transmission: value(1.0), ..Principled::from_color(vec3(0.25, 1.0, 1.0)) };
+        for front_face in [true, false] {
+            let refraction_ratio = if front_face { 1.0 / 1.5 } else { 1.5 };
+            let refracted = (-&incoming).refract(&vec3(0.0, 0.0, 1.0), refraction_ratio);
+            for (attenuation, direction, scattered) in scatter_many(&glass, &ray_towards_origin(&incoming), &hit_record(front_face), 1000) {
+                let mirrored = vec3(-incoming.x, 0.0, incoming.z);
+                if (&direction - &mirrored).length() < 1e-9 {
+                    assert!((&attenuation - &white()).length() < 1e-9, "Attenuation ({})", attenuation);
+                } else {
+                    assert!(scattered);
+                    assert!((&direction - &refracted).length() < 1e-9, "Scattered to ({})", direction);
+                    assert!((&attenuation - &vec3(0.5, 1.0, 1.0)).length() < 1e-9, "Attenuation ({})", attenuation);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clearcoat_reflects_by_its_fresnel_reflectance() {
+        // On smooth black without a specular reflection of its own, only the clear coat reflects anything.
+        let varnished = Principled { roughness: value(0.0), specular: value(0.0), clearcoat: value(1.0), ..Principled::from_color(vec3(0.0, 0.0, 0.0)) };
+        let count = 100_000;
+        let samples = scatter_many(&varnished, &ray_towards_origin(&vec3(0.0, 0.0, 1.0)), &hit_record(true), count);
+        let reflected = samples.iter().filter(|(attenuation, _, _)| attenuation.x > 0.0).count() as f64 / count as f64;
+        let tolerance = 5.0 * (CLEARCOAT_REFLECTANCE * (1.0 - CLEARCOAT_REFLECTANCE) / count as f64).sqrt();
+        assert!((reflected - CLEARCOAT_REFLECTANCE).abs() < tolerance, "Reflected {} instead of {}", reflected, CLEARCOAT_REFLECTANCE);
+    }
+
+    #[test]
+    fn parameters_are_read_at_the_hit() {
+        // Metal on the even squares, and plastic on the odd ones.
+        let material = Principled { metallic: checker(0.0, 1.0), roughness: value(0.0), ..Principled::from_color(white()) };
+        let (even, odd) = checker_hits();
+        assert!(mirrors(&material, &even) && !mirrors(&material, &odd));
+    }
+
+    #[test]
+    fn white_furnace_loses_little_energy() {
+        // The Disney diffuse lobe doesn't conserve energy exactly, so only metal and glass are held to it.
+        for material in [
+            Principled { metallic: value(1.0), ..Principled::from_color(white()) },
+            Principled { transmission: value(1.0), ..Principled::from_color(white()) },
+        ] {
+            let radiance = white_furnace(Arc::new(material), 2000);
+            assert!(radiance > 0.8 && radiance <= 1.0, "White furnace gave {}", radiance);
+        }
+    }
+}
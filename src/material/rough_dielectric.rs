@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use super::Material;
+use super::frame::Frame;
+use super::fresnel::fresnel_dielectric;
+use super::microfacet::TrowbridgeReitz;
+use crate::hittable::HitRecord;
+use crate::texture::{SolidColor, Texture};
+use crate::vec3::{Color, Point3};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+
+/// Frosted glass: a dielectric with a rough surface of GGX microfacets, each of which reflects or refracts like
+/// `Dielectric` with exact Fresnel.
+pub struct RoughDielectric {
+    /// Read with `Texture::scalar`.
+    pub index_of_refraction: Arc<dyn Texture>,
+    /// Roughness along `HitRecord::tangent` and across it, from 0 to 1. Read with `Texture::scalar`.
+    pub roughness_x: Arc<dyn Texture>,
+    pub roughness_y: Arc<dyn Texture>,
+}
+
+impl RoughDielectric {
+    /// `roughness` goes from 0, smooth glass, to 1.
+    pub fn new(index_of_refraction: f64, roughness: f64) -> Self {
+        let roughness: Arc<dyn Texture> = Arc::new(SolidColor::from_value(roughness));
+        Self { index_of_refraction: Arc::new(SolidColor::from_value(index_of_refraction)), roughness_x: roughness.clone(), roughness_y: roughness }
+    }
+
+    fn distribution(&self, u: f64, v: f64, p: &Point3) -> TrowbridgeReitz {
+        let alpha = |roughness: &Arc<dyn Texture>| TrowbridgeReitz::roughness_to_alpha(roughness.scalar(u, v, p));
+        TrowbridgeReitz { alpha_x: alpha(&self.roughness_x), alpha_y: alpha(&self.roughness_y) }
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        let frame = Frame::from_z_and_tangent(&hit_record.normal, &hit_record.tangent);
+        let wo = frame.to_local(&-ray_in.direction.unit_vector());
+        let u = sampler.get_2d();
+        let u_fresnel = sampler.get_1d();
+        if wo.z <= 0.0 {
+            return false;
+        }
+
+        let (u_hit, v_hit, p) = (hit_record.u, hit_record.v, &hit_record.p);
+        let index_of_refraction = self.index_of_refraction.scalar(u_hit, v_hit, p);
+        let refraction_ratio = if hit_record.front_face { 1.0 / index_of_refraction } else { index_of_refraction };
+        let distribution = self.distribution(u_hit, v_hit, p);
+        let wm = distribution.sample_normal(&wo, u);
+        // Choosing between reflection and refraction with the Fresnel reflectance as probability cancels it out.
+        let reflect = u_fresnel < fresnel_dielectric(wo.dot(&wm), refraction_ratio);
+        let wi = if reflect { (-&wo).reflect(&wm) } else { (-&wo).refract(&wm, refraction_ratio) };
+        let weight = distribution.masking_weight(&wo, &wi);
+        *attenuation = Color { x: weight, y: weight, z: weight };
+
+        *scattered = Ray { origin: hit_record.p.clone(), direction: frame.to_world(&wi), time: ray_in.time, wavelengths: ray_in.wavelengths };
+        // A microfacet can send light to the wrong side of the surface, where it is lost.
+        if reflect { wi.z > 0.0 } else { wi.z < 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::material::test_util::{assert_matches_pdf, hit_record, ray_towards_origin, scatter_many, vec3, white_furnace};
+    use crate::vec3::Vec3;
+
+    const GLASS: f64 = 1.5;
+
+    #[test]
+    fn smooth_reflects_or_refracts_like_smooth_glass() {
+        let incoming = vec3(0.6, 0.0, 0.8);
+        let glass = RoughDielectric::new(GLASS, 0.0);
+        let refracted = (-&incoming).refract(&vec3(0.0, 0.0, 1.0), 1.0 / GLASS);
+        for (attenuation, direction, scattered) in scatter_many(&glass, &ray_towards_origin(&incoming), &hit_record(true), 1000) {
+            assert!(scattered);
+            assert_eq!(attenuation.x, 1.0);
+            let mirrored = vec3(-incoming.x, 0.0, incoming.z);
+            assert!((&direction - &mirrored).length() < 1e-9 || (&direction - &refracted).length() < 1e-9, "Scattered to ({})", direction);
+        }
+    }
+
+    #[test]
+    fn rough_scattering_is_distributed_like_visible_normals() {
+        let incoming = vec3(0.4, 0.3, 0.8).unit_vector();
+        let glass = RoughDielectric::new(GLASS, 0.3f64.sqrt());
+        let distribution = glass.distribution(0.0, 0.0, &Point3::new());
+        // Into the glass and out of it, where some microfacets reflect everything.
+        for (front_face, refraction_ratio) in [(true, 1.0 / GLASS), (false, GLASS)] {
+            let samples = scatter_many(&glass, &ray_towards_origin(&incoming), &hit_record(front_face), 100_000);
+            // Scattering to the wrong side is absorbed, but it is still drawn from the same distribution.
+            let directions: Vec<Vec3> = samples.into_iter().map(|(_, direction, _)| direction).collect();
+            assert_matches_pdf(&directions, |wi| {
+                // Reflecting about the half vector, with its Jacobian.
+                let wm = (&incoming + wi).unit_vector();
+                let reflected = fresnel_dielectric(incoming.dot(&wm), refraction_ratio) * distribution.g1(&incoming) * distribution.d(&wm) / (4.0 * incoming.z);
+                // Refracting through the generalized half vector, with its Jacobian.
+                let mut wm = (wi / refraction_ratio + &incoming).unit_vector();
+                if wm.z < 0.0 {
+                    wm = -wm;
+                }
+                let (cos_o, cos_i) = (incoming.dot(&wm), wi.dot(&wm));
+                if cos_o <= 0.0 || cos_i >= 0.0 {
+                    return reflected;
+                }
+                let visible = distribution.g1(&incoming) * cos_o * distribution.d(&wm) / incoming.z;
+                let refracted = (1.0 - fresnel_dielectric(cos_o, refraction_ratio)) * visible * -cos_i / (cos_i + cos_o * refraction_ratio).powi(2);
+                reflected + refracted
+            });
+        }
+    }
+
+    #[test]
+    fn white_furnace_loses_little_energy() {
+        // Light that microfacets send to the wrong side, or bounce between them, is lost, but none may be added.
+        let radiance = white_furnace(Arc::new(RoughDielectric::new(GLASS, 0.5)), 2000);
+        assert!(radiance > 0.8 && radiance <= 1.0, "White furnace gave {}", radiance);
+    }
+}
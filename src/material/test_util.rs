@@ -0,0 +1,176 @@
+//! Statistical checks shared by the tests of the individual materials.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use super::Material;
+use crate::hittable::{HitRecord, HittableBox, HittableList, Sphere};
+use crate::ray::Ray;
+use crate::spectrum::Wavelengths;
+use crate::texture::{CheckerTexture, Texture};
+use crate::sampler::IndependentSampler;
+use crate::vec3::{Color, Point3, Vec3};
+
+// Histogram resolution for the chi-square test, over cos(theta) in [-1, 1] and phi in [0, 2 pi).
+const THETA_BINS: usize = 16;
+const PHI_BINS: usize = 32;
+// Points per bin side when integrating the PDF over a bin.
+const INTEGRATION_STEPS: usize = 8;
+// Bins expected to get fewer samples than this are pooled, since the chi-square statistic is unreliable for them.
+const MIN_EXPECTED: f64 = 5.0;
+// Standard normal quantile for the significance level of the test, 1e-4.
+const SIGNIFICANCE_Z: f64 = 3.719;
+
+pub fn sampler() -> IndependentSampler {
+    IndependentSampler::new(1, 0x5eed)
+}
+
+pub fn vec3(x: f64, y: f64, z: f64) -> Vec3 {
+    Vec3 { x, y, z }
+}
+
+pub fn white() -> Color {
+    vec3(1.0, 1.0, 1.0)
+}
+
+/// A hit at the origin on a surface whose normal, as seen by the ray, is +z.
+pub fn hit_record(front_face: bool) -> HitRecord {
+    let mut hit_record = HitRecord::new();
+    hit_record.normal = vec3(0.0, 0.0, 1.0);
+    hit_record.front_face = front_face;
+    hit_record
+}
+
+/// A ray arriving at the origin from `incoming`, which points away from the surface.
+pub fn ray_towards_origin(incoming: &Vec3) -> Ray {
+    Ray { origin: incoming.clone(), direction: -incoming, time: 0.0, wavelengths: Wavelengths::Rgb }
+}
+
+/// A checker texture of `odd` and `even` squares, for checking that a material reads a parameter at the hit.
+pub fn checker_colors(odd: Color, even: Color) -> Arc<dyn Texture> {
+    Arc::new(CheckerTexture::from_colors(odd, even))
+}
+
+pub fn checker(odd: f64, even: f64) -> Arc<dyn Texture> {
+    checker_colors(vec3(odd, odd, odd), vec3(even, even, even))
+}
+
+/// Like `hit_record(true)`, but on an even and an odd square of `checker`.
+pub fn checker_hits() -> (HitRecord, HitRecord) {
+    let at = |x| HitRecord { p: vec3(x, 0.1, 0.1), ..hit_record(true) };
+    (at(0.1), at(-0.1))
+}
+
+/// Whether `material` only ever reflects light arriving at an angle in the mirror direction.
+pub fn mirrors(material: &dyn Material, hit_record: &HitRecord) -> bool {
+    let samples = scatter_many(material, &ray_towards_origin(&vec3(0.0, 0.6, 0.8)), hit_record, 100);
+    samples.iter().all(|(_, direction, _)| (direction - &vec3(0.0, -0.6, 0.8)).length() < 1e-9)
+}
+
+/// Scatters `count` times and returns the attenuation, scattered direction and whether `scatter` returned true.
+pub fn scatter_many(material: &dyn Material, ray_in: &Ray, hit_record: &HitRecord, count: usize) -> Vec<(Color, Vec3, bool)> {
+    let mut sampler = sampler();
+    (0..count)
+        .map(|_| {
+            let mut attenuation = Color::new();
+            let mut scattered = Ray::new();
+            let scattered_at_all = material.scatter(ray_in, hit_record, &mut attenuation, &mut scattered, &mut sampler);
+            (attenuation, scattered.direction, scattered_at_all)
+        })
+        .collect()
+}
+
+fn direction(cos_theta: f64, phi: f64) -> Vec3 {
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    vec3(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+fn bin_index(direction: &Vec3) -> usize {
+    let direction = direction.unit_vector();
+    let theta_bin = (((direction.z + 1.0) / 2.0 * THETA_BINS as f64) as usize).min(THETA_BINS - 1);
+    let mut phi = direction.y.atan2(direction.x);
+    if phi < 0.0 {
+        phi += 2.0 * PI;
+    }
+    let phi_bin = ((phi / (2.0 * PI) * PHI_BINS as f64) as usize).min(PHI_BINS - 1);
+    theta_bin * PHI_BINS + phi_bin
+}
+
+/// Upper quantile of the chi-square distribution with `dof` degrees of freedom (Wilson-Hilferty).
+fn chi_square_critical_value(dof: f64) -> f64 {
+    let a = 2.0 / (9.0 * dof);
+    dof * (1.0 - a + SIGNIFICANCE_Z * a.sqrt()).powi(3)
+}
+
+/// Pearson's chi-square test of the sampled `directions` against `pdf`, a density over the whole sphere of
+/// directions with respect to solid angle. Panics if the samples are unlikely to come from `pdf`.
+pub fn assert_matches_pdf(directions: &[Vec3], pdf: impl Fn(&Vec3) -> f64) {
+    let mut observed = vec![0.0; THETA_BINS * PHI_BINS];
+    for direction in directions {
+        observed[bin_index(direction)] += 1.0;
+    }
+
+    let bin_cos_theta = 2.0 / THETA_BINS as f64;
+    let bin_phi = 2.0 * PI / PHI_BINS as f64;
+    let mut expected = vec![0.0; THETA_BINS * PHI_BINS];
+    for theta_bin in 0..THETA_BINS {
+        for phi_bin in 0..PHI_BINS {
+            let mut integral = 0.0;
+            for i in 0..INTEGRATION_STEPS {
+                for j in 0..INTEGRATION_STEPS {
+                    let cos_theta = -1.0 + (theta_bin as f64 + (i as f64 + 0.5) / INTEGRATION_STEPS as f64) * bin_cos_theta;
+                    let phi = (phi_bin as f64 + (j as f64 + 0.5) / INTEGRATION_STEPS as f64) * bin_phi;
+                    integral += pdf(&direction(cos_theta, phi));
+                }
+            }
+            // Solid angle is d(cos theta) d(phi).
+            let solid_angle = bin_cos_theta * bin_phi / (INTEGRATION_STEPS * INTEGRATION_STEPS) as f64;
+            expected[theta_bin * PHI_BINS + phi_bin] = integral * solid_angle * directions.len() as f64;
+        }
+    }
+
+    let mut chi_square = 0.0;
+    let mut dof = 0;
+    let (mut pooled_observed, mut pooled_expected) = (0.0, 0.0);
+    for (observed, expected) in observed.iter().zip(&expected) {
+        if *expected < MIN_EXPECTED {
+            pooled_observed += observed;
+            pooled_expected += expected;
+        } else {
+            chi_square += (observed - expected).powi(2) / expected;
+            dof += 1;
+        }
+    }
+    if pooled_expected > MIN_EXPECTED {
+        chi_square += (pooled_observed - pooled_expected).powi(2) / pooled_expected;
+        dof += 1;
+    } else {
+        assert!(pooled_observed < MIN_EXPECTED * 4.0, "{} samples landed where the PDF is (nearly) zero", pooled_observed);
+    }
+    assert!(dof > 1, "Too few populated bins for a chi-square test!");
+    let critical = chi_square_critical_value((dof - 1) as f64);
+    assert!(chi_square < critical, "Chi-square statistic {:.1} exceeds {:.1} ({} degrees of freedom)", chi_square, critical, dof - 1);
+}
+
+/// Renders paths into a closed-off corner of white walls and a sphere, all made of `material`, under a uniform
+/// white sky, and returns the mean radiance. Anything that neither absorbs nor emits must give exactly 1.
+pub fn white_furnace(material: Arc<dyn Material>, paths: usize) -> f64 {
+    let mut world = HittableList::new();
+    world.add(Arc::new(Sphere { center: vec3(0.0, 1.0, 0.0), radius: 1.0, material: material.clone() }));
+    world.add(Arc::new(HittableBox::new(vec3(-3.0, -1.0, -3.0), vec3(3.0, 0.0, 3.0), material.clone())));
+    world.add(Arc::new(HittableBox::new(vec3(-3.0, 0.0, -3.0), vec3(-2.0, 3.0, 3.0), material)));
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut sampler = sampler();
+    let mut total = 0.0;
+    for _ in 0..paths {
+        // Start above everything, so no path starts inside a closed object and gets trapped there.
+        let origin = Point3 { x: rng.gen_range(-3.0..3.0), y: rng.gen_range(3.5..5.0), z: rng.gen_range(-3.0..3.0) };
+        let target = Point3 { x: rng.gen_range(-2.0..1.0), y: rng.gen_range(-0.5..2.0), z: rng.gen_range(-1.0..1.0) };
+        let ray = Ray { direction: &target - &origin, origin, time: 0.0, wavelengths: Wavelengths::Rgb };
+        let color = crate::render::ray_color(&ray, &white(), &world, 200, &mut sampler);
+        total += (color.x + color.y + color.z) / 3.0;
+    }
+    total / paths as f64
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use super::Material;
+use super::fresnel::fresnel_dielectric;
+use crate::hittable::HitRecord;
+use crate::texture::{SolidColor, Texture};
+use crate::vec3::Color;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+
+/// A thin sheet of glass, like a window pane, modelled as a single surface. Light either reflects off it, counting
+/// the light that bounces back and forth between the two sides of the sheet, or passes straight through.
+pub struct ThinDielectric {
+    /// Read with `Texture::scalar`.
+    pub index_of_refraction: Arc<dyn Texture>,
+}
+
+impl ThinDielectric {
+    pub fn new(index_of_refraction: f64) -> Self {
+        Self { index_of_refraction: Arc::new(SolidColor::from_value(index_of_refraction)) }
+    }
+}
+
+// The fraction of light a sheet with the given index of refraction reflects.
+fn reflectance(cos_theta: f64, index_of_refraction: f64) -> f64 {
+    let r = fresnel_dielectric(cos_theta, 1.0 / index_of_refraction);
+    // Only the first reflection off the front, plus every one that makes it back out through the front.
+    if r < 1.0 { r + (1.0 - r).powi(2) * r / (1.0 - r * r) } else { r }
+}
+
+impl Material for ThinDielectric {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord, attenuation: &mut Color, scattered: &mut Ray, sampler: &mut dyn Sampler) -> bool {
+        *attenuation = Color { x: 1.0, y: 1.0, z: 1.0 };
+        let unit_direction = ray_in.direction.unit_vector();
+        let cos_theta = hit_record.normal.dot(&-&unit_direction).min(1.0);
+        let index_of_refraction = self.index_of_refraction.scalar(hit_record.u, hit_record.v, &hit_record.p);
+        let direction = if sampler.get_1d() < reflectance(cos_theta, index_of_refraction) { unit_direction.reflect(&hit_record.normal) } else { unit_direction };
+        *scattered = Ray { origin: hit_record.p.clone(), direction, time: ray_in.time, wavelengths: ray_in.wavelengths };
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::material::test_util::{hit_record, ray_towards_origin, scatter_many, vec3, white_furnace};
+
+    const GLASS: f64 = 1.5;
+    const SAMPLES: usize = 100_000;
+
+    fn pane() -> ThinDielectric {
+        ThinDielectric::new(GLASS)
+    }
+
+    #[test]
+    fn passes_straight_through_or_reflects() {
+        let incoming = vec3(0.3, -0.4, 0.8).unit_vector();
+        for front_face in [true, false] {
+            for (_, direction, scattered) in scatter_many(&pane(), &ray_towards_origin(&incoming), &hit_record(front_face), 1000) {
+                assert!(scattered);
+                let through = (&direction + &incoming).length() < 1e-12;
+                let mirrored = (&direction - &vec3(-incoming.x, -incoming.y, incoming.z)).length() < 1e-12;
+                assert!(through || mirrored, "Scattered to ({})", direction);
+            }
+        }
+    }
+
+    #[test]
+    fn reflects_off_both_sides_at_normal_incidence() {
+        let samples = scatter_many(&pane(), &ray_towards_origin(&vec3(0.0, 0.0, 1.0)), &hit_record(true), SAMPLES);
+        let reflected = samples.iter().filter(|(_, direction, _)| direction.z > 0.0).count() as f64 / SAMPLES as f64;
+        // The geometric series of bounces inside the sheet sums to 2R / (1 + R).
+        let r = ((GLASS - 1.0) / (GLASS + 1.0)).powi(2);
+        let expected = 2.0 * r / (1.0 + r);
+        let tolerance = 5.0 * (expected * (1.0 - expected) / SAMPLES as f64).sqrt();
+        assert!((reflected - expected).abs() < tolerance, "Reflected {} instead of {}", reflected, expected);
+    }
+
+    #[test]
+    fn white_furnace_conserves_energy() {
+        let radiance = white_furnace(Arc::new(pane()), 2000);
+        assert!((radiance - 1.0).abs() < 1e-3, "White furnace gave {}", radiance);
+    }
+}
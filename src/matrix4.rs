@@ -0,0 +1,217 @@
+use auto_ops::impl_op_ex;
+
+use crate::quaternion::Quaternion;
+use crate::util::degrees_to_radians;
+use crate::vec3::{Point3, Vec3};
+
+/// A row-major 4x4 matrix used for affine transforms of points, vectors and normals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix4 {
+    pub m: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn identity() -> Matrix4 {
+        Matrix4 {
+            m: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn translation(offset: &Vec3) -> Matrix4 {
+        Matrix4 {
+            m: [
+                [1.0, 0.0, 0.0, offset.x],
+                [0.0, 1.0, 0.0, offset.y],
+                [0.0, 0.0, 1.0, offset.z],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn scale(factors: &Vec3) -> Matrix4 {
+        Matrix4 {
+            m: [
+                [factors.x, 0.0, 0.0, 0.0],
+                [0.0, factors.y, 0.0, 0.0],
+                [0.0, 0.0, factors.z, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn uniform_scale(factor: f64) -> Matrix4 {
+        Self::scale(&Vec3 { x: factor, y: factor, z: factor })
+    }
+
+    /// Shear where each coordinate is offset proportionally to the other two, e.g. `x' = x + xy * y + xz * z`.
+    pub fn shear(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Matrix4 {
+        Matrix4 {
+            m: [
+                [1.0, xy, xz, 0.0],
+                [yx, 1.0, yz, 0.0],
+                [zx, zy, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Rotation about the x axis. The angle is in degrees, like the rest of the scene description.
+    pub fn rotation_x(angle: f64) -> Matrix4 {
+        let (sin_theta, cos_theta) = degrees_to_radians(angle).sin_cos();
+        Matrix4 {
+            m: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, cos_theta, -sin_theta, 0.0],
+                [0.0, sin_theta, cos_theta, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn rotation_y(angle: f64) -> Matrix4 {
+        let (sin_theta, cos_theta) = degrees_to_radians(angle).sin_cos();
+        Matrix4 {
+            m: [
+                [cos_theta, 0.0, sin_theta, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [-sin_theta, 0.0, cos_theta, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn rotation_z(angle: f64) -> Matrix4 {
+        let (sin_theta, cos_theta) = degrees_to_radians(angle).sin_cos();
+        Matrix4 {
+            m: [
+                [cos_theta, -sin_theta, 0.0, 0.0],
+                [sin_theta, cos_theta, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Rotation by `angle` degrees about an arbitrary axis through the origin.
+    pub fn rotation(axis: &Vec3, angle: f64) -> Matrix4 {
+        Quaternion::from_axis_angle(axis, angle).to_matrix()
+    }
+
+    /// Rotation from Euler angles in degrees, applied in x, y, z order.
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Matrix4 {
+        Self::rotation_z(z) * Self::rotation_y(y) * Self::rotation_x(x)
+    }
+
+    pub fn from_quaternion(rotation: &Quaternion) -> Matrix4 {
+        rotation.to_matrix()
+    }
+
+    /// Places an object at `from`, with its -z axis pointing at `to` and its y axis as close to `up` as possible.
+    /// This is the same basis `Camera` builds from `lookfrom`, `lookat` and `vup`.
+    pub fn look_at(from: &Point3, to: &Point3, up: &Vec3) -> Matrix4 {
+        let w = (from - to).unit_vector();
+        let u = up.cross(&w).unit_vector();
+        let v = w.cross(&u);
+        Matrix4 {
+            m: [
+                [u.x, v.x, w.x, from.x],
+                [u.y, v.y, w.y, from.y],
+                [u.z, v.z, w.z, from.z],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn transpose(&self) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Matrix4 { m }
+    }
+
+    /// Inverse by Gauss-Jordan elimination with partial pivoting. Returns `None` for singular matrices.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let mut a = self.m;
+        let mut inv = Self::identity().m;
+        for column in 0..4 {
+            let pivot = (column..4).max_by(|&i, &j| a[i][column].abs().partial_cmp(&a[j][column].abs()).unwrap())?;
+            if a[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(column, pivot);
+            inv.swap(column, pivot);
+            let scale = 1.0 / a[column][column];
+            for j in 0..4 {
+                a[column][j] *= scale;
+                inv[column][j] *= scale;
+            }
+            for row in 0..4 {
+                if row != column {
+                    let factor = a[row][column];
+                    for j in 0..4 {
+                        a[row][j] -= factor * a[column][j];
+                        inv[row][j] -= factor * inv[column][j];
+                    }
+                }
+            }
+        }
+        Some(Matrix4 { m: inv })
+    }
+
+    pub fn transform_point(&self, p: &Point3) -> Point3 {
+        let m = &self.m;
+        let x = m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3];
+        let y = m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3];
+        let z = m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3];
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        if w == 1.0 {
+            Point3 { x, y, z }
+        } else {
+            Point3 { x: x / w, y: y / w, z: z / w }
+        }
+    }
+
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3 {
+            x: m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            y: m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            z: m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        }
+    }
+
+    /// Normals transform by the inverse transpose, so this must be called on the *inverse* of the matrix
+    /// that transforms points. The result is not normalized.
+    pub fn transform_normal(&self, n: &Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3 {
+            x: m[0][0] * n.x + m[1][0] * n.y + m[2][0] * n.z,
+            y: m[0][1] * n.x + m[1][1] * n.y + m[2][1] * n.z,
+            z: m[0][2] * n.x + m[1][2] * n.y + m[2][2] * n.z,
+        }
+    }
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl_op_ex!(*|lhs: &Matrix4, rhs: &Matrix4| -> Matrix4 {
+    let mut m = [[0.0; 4]; 4];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| lhs.m[i][k] * rhs.m[k][j]).sum();
+        }
+    }
+    Matrix4 { m }
+});
@@ -0,0 +1,86 @@
+use std::time::{Duration, Instant};
+
+use crate::stats::{format_duration, format_rate, per_second, RenderStats};
+
+// Least time between two updates of the console progress line.
+const CONSOLE_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How far a render has come.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    pub rows_done: u32,
+    pub rows: u32,
+    pub samples_done: u64,
+    pub samples: u64,
+    pub rays: u64,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f64 {
+        self.samples_done as f64 / self.samples.max(1) as f64
+    }
+
+    /// Time left, assuming the rest goes as fast as what is done so far. `None` until there is anything to go by.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.samples_done == 0 {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((self.samples - self.samples_done) as f64 / self.samples_done as f64))
+    }
+
+    pub fn samples_per_second(&self) -> f64 {
+        per_second(self.samples_done, self.elapsed)
+    }
+
+    pub fn rays_per_second(&self) -> f64 {
+        per_second(self.rays, self.elapsed)
+    }
+}
+
+/// Gets told how a render is going. `update` is called on the thread that called `render_with_progress`,
+/// after every row.
+pub trait ProgressReporter {
+    fn update(&mut self, progress: &Progress);
+    fn finish(&mut self, _: &RenderStats) {}
+}
+
+/// Reports nothing.
+pub struct SilentProgress;
+
+impl ProgressReporter for SilentProgress {
+    fn update(&mut self, _: &Progress) {}
+}
+
+/// Keeps a progress line up to date on stderr, and prints the stats summary when done.
+#[derive(Default)]
+pub struct ConsoleProgress {
+    last_update: Option<Instant>,
+}
+
+impl ConsoleProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProgressReporter for ConsoleProgress {
+    fn update(&mut self, progress: &Progress) {
+        let done = progress.rows_done == progress.rows;
+        if !done && self.last_update.is_some_and(|last_update| last_update.elapsed() < CONSOLE_UPDATE_INTERVAL) {
+            return;
+        }
+        self.last_update = Some(Instant::now());
+        let eta = progress.eta().map(format_duration).unwrap_or_else(|| String::from("?"));
+        // Trailing spaces clear what is left of a longer previous line.
+        eprint!(
+            "\r{:6.2}% | elapsed {} | ETA {} | {} samples/s | {} rays/s    ",
+            progress.fraction() * 100.0, format_duration(progress.elapsed), eta, format_rate(progress.samples_per_second()), format_rate(progress.rays_per_second())
+        );
+    }
+
+    fn finish(&mut self, stats: &RenderStats) {
+        eprintln!();
+        eprintln!("{}", stats);
+    }
+}
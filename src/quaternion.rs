@@ -0,0 +1,118 @@
+use auto_ops::impl_op_ex;
+
+use crate::matrix4::Matrix4;
+use crate::util::degrees_to_radians;
+use crate::vec3::Vec3;
+
+/// A rotation stored as a unit quaternion `w + xi + yj + zk`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    pub fn identity() -> Quaternion {
+        Quaternion { w: 1.0, x: 0.0, y: 0.0, z: 0.0 }
+    }
+
+    /// Rotation by `angle` degrees about `axis`, which does not need to be normalized.
+    pub fn from_axis_angle(axis: &Vec3, angle: f64) -> Quaternion {
+        let axis = axis.unit_vector();
+        let (sin_half, cos_half) = (degrees_to_radians(angle) / 2.0).sin_cos();
+        Quaternion {
+            w: cos_half,
+            x: axis.x * sin_half,
+            y: axis.y * sin_half,
+            z: axis.z * sin_half,
+        }
+    }
+
+    /// Rotation from Euler angles in degrees, applied in x, y, z order. Matches `Matrix4::from_euler`.
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Quaternion {
+        let x_axis = Vec3 { x: 1.0, y: 0.0, z: 0.0 };
+        let y_axis = Vec3 { x: 0.0, y: 1.0, z: 0.0 };
+        let z_axis = Vec3 { x: 0.0, y: 0.0, z: 1.0 };
+        Self::from_axis_angle(&z_axis, z) * Self::from_axis_angle(&y_axis, y) * Self::from_axis_angle(&x_axis, x)
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalized(&self) -> Quaternion {
+        let length = self.length();
+        Quaternion { w: self.w / length, x: self.x / length, y: self.y / length, z: self.z / length }
+    }
+
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion { w: self.w, x: -self.x, y: -self.y, z: -self.z }
+    }
+
+    /// Spherical linear interpolation along the shorter arc between `self` (t = 0) and `other` (t = 1).
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Quaternion {
+        let mut cos_theta = self.dot(other);
+        let mut other = other.clone();
+        if cos_theta < 0.0 {
+            // q and -q are the same rotation; flip one to take the short way round.
+            cos_theta = -cos_theta;
+            other = Quaternion { w: -other.w, x: -other.x, y: -other.y, z: -other.z };
+        }
+        let (a, b) = if cos_theta > 0.9995 {
+            // Nearly parallel: fall back to lerp to avoid dividing by sin(theta) ~ 0.
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (((1.0 - t) * theta).sin() / sin_theta, (t * theta).sin() / sin_theta)
+        };
+        Quaternion {
+            w: a * self.w + b * other.w,
+            x: a * self.x + b * other.x,
+            y: a * self.y + b * other.y,
+            z: a * self.z + b * other.z,
+        }.normalized()
+    }
+
+    /// Angle of the rotation between `self` and `other`, in radians.
+    pub fn angle_to(&self, other: &Quaternion) -> f64 {
+        2.0 * self.dot(other).abs().min(1.0).acos()
+    }
+
+    pub fn rotate(&self, v: &Vec3) -> Vec3 {
+        self.to_matrix().transform_vector(v)
+    }
+
+    pub fn to_matrix(&self) -> Matrix4 {
+        let Quaternion { w, x, y, z } = self.normalized();
+        Matrix4 {
+            m: [
+                [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y), 0.0],
+                [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x), 0.0],
+                [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y), 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl_op_ex!(*|lhs: &Quaternion, rhs: &Quaternion| -> Quaternion {
+    Quaternion {
+        w: lhs.w * rhs.w - lhs.x * rhs.x - lhs.y * rhs.y - lhs.z * rhs.z,
+        x: lhs.w * rhs.x + lhs.x * rhs.w + lhs.y * rhs.z - lhs.z * rhs.y,
+        y: lhs.w * rhs.y - lhs.x * rhs.z + lhs.y * rhs.w + lhs.z * rhs.x,
+        z: lhs.w * rhs.z + lhs.x * rhs.y - lhs.y * rhs.x + lhs.z * rhs.w,
+    }
+});
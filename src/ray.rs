@@ -1,9 +1,13 @@
+use crate::spectrum::Wavelengths;
 use crate::vec3::{Point3, Vec3};
 
+#[derive(Default)]
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
     pub time: f64,
+    /// What the colors along the path mean: RGB, or radiance at the wavelengths it carries.
+    pub wavelengths: Wavelengths,
 }
 
 impl Ray {
@@ -11,7 +15,8 @@ impl Ray {
         Ray {
             origin: Point3 { x: 0.0, y: 0.0, z: 0.0 },
             direction: Vec3 { x: 0.0, y:0.0, z: 0.0},
-            time: 0.0
+            time: 0.0,
+            wavelengths: Wavelengths::Rgb,
         }
     }
 
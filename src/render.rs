@@ -0,0 +1,257 @@
+use std::{collections::BTreeMap, panic, sync::{Arc, atomic::{AtomicU32, Ordering}, mpsc}, thread, time::Instant};
+
+use crate::camera::Camera;
+use crate::film::Film;
+use crate::filter::{BoxFilter, Filter};
+use crate::hittable::Hittable;
+use crate::image::{ColorSpace, Image};
+use crate::progress::{Progress, ProgressReporter, SilentProgress};
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::sampler::{Sampler, SamplerKind};
+use crate::spectrum::Wavelengths;
+use crate::stats::{self, Counters, RenderStats};
+use crate::vec3::Color;
+
+/// How an image gets rendered.
+#[derive(Clone)]
+pub struct RenderSettings {
+    /// Size of the output image in pixels. The camera's aspect ratio should match, or the picture is stretched.
+    pub image_width: u32,
+    pub image_height: u32,
+    pub samples_per_pixel: u32,
+    /// Bounces after which a path is cut off and counted as black.
+    pub max_depth: u32,
+    pub thread_count: u32,
+    pub sampler: SamplerKind,
+    /// Renders with the same seed and settings are identical, whatever the thread count.
+    pub seed: u64,
+    /// Reconstruction filter used to turn samples into pixels.
+    pub filter: Arc<dyn Filter>,
+    /// Traces wavelengths rather than RGB, and returns the image in CIE XYZ. Slower and noisier, but mixes colors
+    /// the way light does, and shows dispersion without the extra noise of narrowing RGB paths down.
+    pub spectral: bool,
+}
+
+impl RenderSettings {
+    /// The height of an image `image_width` pixels wide with `aspect_ratio`, to the nearest pixel and at least 2.
+    pub fn image_height_for(image_width: u32, aspect_ratio: f64) -> u32 {
+        ((image_width as f64 / aspect_ratio).round() as u32).max(2)
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            image_width: 600,
+            image_height: Self::image_height_for(600, 16.0 / 9.0),
+            samples_per_pixel: 30,
+            max_depth: 50,
+            thread_count: 8,
+            sampler: SamplerKind::Sobol,
+            seed: 0,
+            filter: Arc::new(BoxFilter::default()),
+            spectral: false,
+        }
+    }
+}
+
+struct PixelUpdate {
+    color: Color,
+    x: f64,
+    y: f64,
+}
+
+// Every sample of one image row, counted from the top, and the work it took.
+struct RowUpdate {
+    row: u32,
+    samples: Vec<PixelUpdate>,
+    counters: Counters,
+}
+
+pub(crate) fn ray_color(ray: &Ray, background: &Color, world: &dyn Hittable, depth: u32, sampler: &mut dyn Sampler) -> Color {
+    if depth == 0 {
+        return Color {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        };
+    }
+    stats::count_ray();
+    // Materials, textures and the background all work in RGB, whatever the path carries.
+    match world.hit(ray, 0.001, f64::INFINITY, sampler) {
+        None => ray.wavelengths.illuminant(background),
+        Some(rec) => {
+            let mut scattered = Ray::new();
+            let mut attenuation = Color::new();
+            if let Some(material) = &rec.material {
+                let emitted = ray.wavelengths.illuminant(&material.emitted(&rec));
+                if !material.scatter(ray, &rec, &mut attenuation, &mut scattered, sampler) {
+                    emitted
+                } else {
+                    let attenuation = ray.wavelengths.reflectance(&attenuation) * ray.wavelengths.narrowing_weight(&scattered.wavelengths);
+                    emitted + attenuation * ray_color(&scattered, background, world, depth - 1, sampler)
+                }
+            } else {
+                Color { x: 0.0, y: 0.0, z: 0.0 }
+            }
+        },
+    }
+}
+
+// Threads take whole rows, top first, from `next_row` until there are none left.
+fn render_rows(scene: &Scene, camera: &dyn Camera, settings: &RenderSettings, mut sampler: Box<dyn Sampler>, next_row: &AtomicU32, tx: mpsc::Sender<RowUpdate>) {
+    let (image_width, image_height) = (settings.image_width, settings.image_height);
+    // Start counting from zero, whatever this thread did before.
+    stats::take_counters();
+    loop {
+        let row = next_row.fetch_add(1, Ordering::Relaxed);
+        if row >= image_height {
+            break;
+        }
+        let j = image_height - 1 - row;
+        let mut samples = Vec::with_capacity((image_width * sampler.samples_per_pixel()) as usize);
+        for i in 0..image_width {
+            for index in 0..sampler.samples_per_pixel() {
+                sampler.start_pixel_sample(i, j, index);
+                let (dx, dy) = sampler.get_2d();
+                let x = (i as f64) + dx;
+                let y = (j as f64) + dy;
+                let u = x / ((image_width - 1) as f64);
+                let v = y / ((image_height - 1) as f64);
+                let color = match camera.get_ray(u, v, sampler.as_mut()) {
+                    Some(ray) if settings.spectral => {
+                        let ray = Ray { wavelengths: Wavelengths::sample_spectral(sampler.get_1d()), ..ray };
+                        let radiance = ray_color(&ray, &scene.background, scene.world.as_ref(), settings.max_depth, sampler.as_mut());
+                        ray.wavelengths.to_xyz(&radiance)
+                    }
+                    Some(ray) => ray_color(&ray, &scene.background, scene.world.as_ref(), settings.max_depth, sampler.as_mut()),
+                    None => Color::new(),
+                };
+
+                samples.push(PixelUpdate { color, x, y });
+            }
+        }
+        tx.send(RowUpdate { row, samples, counters: stats::take_counters() }).unwrap();
+    }
+}
+
+/// Renders `scene` as seen by `camera`, on `settings.thread_count` threads, and returns the image in linear color:
+/// RGB, or CIE XYZ for spectral renders.
+pub fn render(scene: &Scene, camera: Arc<dyn Camera>, settings: &RenderSettings) -> Image {
+    render_with_progress(scene, camera, settings, &mut SilentProgress).0
+}
+
+/// Like `render`, but reports progress to `reporter` along the way, and also returns what the render took.
+pub fn render_with_progress(scene: &Scene, camera: Arc<dyn Camera>, settings: &RenderSettings, reporter: &mut dyn ProgressReporter) -> (Image, RenderStats) {
+    assert!(settings.image_width >= 2 && settings.image_height >= 2, "Images must be at least 2 pixels wide and high!");
+    assert!(settings.samples_per_pixel >= 1, "Renders need at least 1 sample per pixel!");
+    let start = Instant::now();
+    let (image_width, image_height) = (settings.image_width, settings.image_height);
+    let sampler = settings.sampler.build(settings.samples_per_pixel, settings.seed);
+    let thread_count = settings.thread_count.max(1);
+    let color_space = if settings.spectral { ColorSpace::Xyz } else { ColorSpace::LinearSrgb };
+    let mut film = Film::new(image_width as usize, image_height as usize, settings.filter.clone()).with_color_space(color_space);
+    let mut progress = Progress {
+        rows_done: 0,
+        rows: image_height,
+        samples_done: 0,
+        samples: image_width as u64 * image_height as u64 * sampler.samples_per_pixel() as u64,
+        rays: 0,
+        elapsed: start.elapsed(),
+    };
+    let mut counters = Counters::default();
+    let (camera, next_row) = (camera.as_ref(), AtomicU32::new(0));
+    thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel::<RowUpdate>();
+        let threads: Vec<_> = (0..thread_count).map(|_| {
+            let (sender, thread_sampler, next_row) = (tx.clone(), sampler.clone_sampler(), &next_row);
+            scope.spawn(move || render_rows(scene, camera, settings, thread_sampler, next_row, sender))
+        }).collect();
+        // Only the render threads hold senders now, so the loop below ends when they all finish, or panic.
+        drop(tx);
+        // Rows finish in whatever order the threads get to them, but are added to the film strictly top to bottom
+        // so the floating point sums, and so the image, don't depend on scheduling.
+        let mut pending = BTreeMap::new();
+        let mut next_to_add = 0;
+        for update in rx {
+            counters += update.counters;
+            progress.rows_done += 1;
+            progress.samples_done += update.samples.len() as u64;
+            progress.rays = counters.rays;
+            progress.elapsed = start.elapsed();
+            reporter.update(&progress);
+            pending.insert(update.row, update.samples);
+            while let Some(samples) = pending.remove(&next_to_add) {
+                for sample in samples {
+                    film.add_sample(sample.x, sample.y, sample.color);
+                }
+                next_to_add += 1;
+            }
+        }
+        // A thread that panicked left rows out, so pass its panic on rather than return a partial image.
+        for thread in threads {
+            if let Err(payload) = thread.join() {
+                panic::resume_unwind(payload);
+            }
+        }
+    });
+    let stats = RenderStats {
+        image_width,
+        image_height,
+        samples_per_pixel: sampler.samples_per_pixel(),
+        thread_count,
+        samples: progress.samples_done,
+        counters,
+        elapsed: start.elapsed(),
+    };
+    reporter.finish(&stats);
+    (film.image(), stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::{HitRecord, HittableList, AABB};
+    use crate::vec3::Point3;
+
+    // Panics on any ray, like a bug in some primitive would.
+    struct Broken;
+
+    impl Hittable for Broken {
+        fn hit(&self, _: &Ray, _: f64, _: f64, _: &mut dyn Sampler) -> Option<HitRecord> {
+            panic!("Broken hittable");
+        }
+
+        fn bounding_box(&self, _: f64, _: f64) -> Option<AABB> {
+            None
+        }
+    }
+
+    fn scene(world: Arc<dyn Hittable>, samples_per_pixel: u32) -> Scene {
+        Scene {
+            settings: RenderSettings { image_width: 8, image_height: 4, samples_per_pixel, thread_count: 2, ..Default::default() },
+            ..Scene::new(world, Point3::new(), Point3 { x: 0.0, y: 0.0, z: -1.0 }, 90.0)
+        }
+    }
+
+    #[test]
+    fn default_height_is_rounded_like_any_other() {
+        assert_eq!(RenderSettings::default().image_height, 338);
+        assert_eq!(RenderSettings::image_height_for(1, 16.0 / 9.0), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1 sample per pixel")]
+    fn rejects_zero_samples_per_pixel() {
+        let scene = scene(Arc::new(HittableList::new()), 0);
+        render(&scene, Arc::new(scene.perspective_camera(0.0, 1.0)), &scene.settings);
+    }
+
+    #[test]
+    #[should_panic(expected = "Broken hittable")]
+    fn passes_on_panics_from_render_threads() {
+        let scene = scene(Arc::new(Broken), 1);
+        render(&scene, Arc::new(scene.perspective_camera(0.0, 1.0)), &scene.settings);
+    }
+}
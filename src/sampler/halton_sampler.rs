@@ -0,0 +1,83 @@
+use super::{hash, hash_to_float, mix_bits, permutation_element, Sampler, ONE_MINUS_EPSILON};
+
+// One base per dimension; dimensions past the end reuse the bases with a different scramble.
+const PRIMES: [u64; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53,
+    59, 61, 67, 71, 73, 79, 83, 89, 97, 101, 103, 107, 109, 113, 127, 131,
+];
+
+/// The Halton sequence, with dimension `d` being the radical inverse of the sample index in the `d`th prime.
+/// Digits are Owen scrambled with a seed per pixel and dimension, so neighbouring pixels don't share patterns.
+#[derive(Clone)]
+pub struct HaltonSampler {
+    samples_per_pixel: u32,
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl HaltonSampler {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Self {
+        Self {
+            samples_per_pixel,
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn next_value(&mut self) -> f64 {
+        let base = PRIMES[self.dimension as usize % PRIMES.len()];
+        let seed = hash(&[self.seed, self.pixel.0 as u64, self.pixel.1 as u64, self.dimension as u64]);
+        self.dimension += 1;
+        scrambled_radical_inverse(base, self.index as u64, self.samples_per_pixel as u64 - 1, seed)
+    }
+}
+
+/// Mirrors the base `base` digits of `a` about the radix point, permuting each digit by a hash of the digits
+/// before it (Owen scrambling). `max_index` is the largest index used, which sets how many digits must be
+/// scrambled for all the indices to stay stratified.
+fn scrambled_radical_inverse(base: u64, mut a: u64, mut max_index: u64, seed: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut inv_base_m = 1.0;
+    let mut reversed_digits: u64 = 0;
+    while a != 0 || max_index != 0 {
+        let next = a / base;
+        let digit_hash = mix_bits(seed ^ reversed_digits);
+        let digit = permutation_element((a - next * base) as u32, base as u32, digit_hash as u32) as u64;
+        reversed_digits = reversed_digits * base + digit;
+        inv_base_m *= inv_base;
+        a = next;
+        max_index /= base;
+    }
+    // Scrambling turns the infinite run of zero digits after these into random digits, which is the same as
+    // adding one uniform random value below the last digit.
+    let tail = hash_to_float(mix_bits(seed ^ reversed_digits ^ 0x5bd1e995));
+    ((reversed_digits as f64 + tail) * inv_base_m).min(ONE_MINUS_EPSILON)
+}
+
+impl Sampler for HaltonSampler {
+    fn samples_per_pixel(&self) -> u32 {
+        self.samples_per_pixel
+    }
+
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = (x, y);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.next_value()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.next_value(), self.next_value())
+    }
+
+    fn clone_sampler(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,47 @@
+use super::{hash, hash_to_float, mix_bits, Sampler};
+
+/// Plain uniform random numbers with no stratification at all; the baseline the other samplers improve on.
+/// Every pixel sample gets its own SplitMix64 stream, keyed by the seed, pixel and sample index.
+#[derive(Clone)]
+pub struct IndependentSampler {
+    samples_per_pixel: u32,
+    seed: u64,
+    state: u64,
+}
+
+impl IndependentSampler {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Self {
+        Self {
+            samples_per_pixel,
+            seed,
+            state: seed,
+        }
+    }
+
+    fn next(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        hash_to_float(mix_bits(self.state))
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn samples_per_pixel(&self) -> u32 {
+        self.samples_per_pixel
+    }
+
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.state = hash(&[self.seed, x as u64, y as u64, index as u64]);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.next()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.next(), self.next())
+    }
+
+    fn clone_sampler(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
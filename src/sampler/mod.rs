@@ -0,0 +1,115 @@
+mod independent_sampler;
+pub use independent_sampler::IndependentSampler;
+mod stratified_sampler;
+pub use stratified_sampler::StratifiedSampler;
+mod halton_sampler;
+pub use halton_sampler::HaltonSampler;
+mod sobol_sampler;
+pub use sobol_sampler::SobolSampler;
+
+use crate::ray::Ray;
+
+// Largest f64 below 1.0, so samples stay in `[0.0, 1.0)`.
+pub(crate) const ONE_MINUS_EPSILON: f64 = 1.0 - f64::EPSILON / 2.0;
+
+/// Hands out the random numbers for one camera sample at a time. Every call to `get_1d` or `get_2d` uses up
+/// the next dimension of the sample, so callers must ask for values in the same order for every sample for
+/// stratification to carry through. So materials draw every value they might need up front, before choosing
+/// between lobes or bailing out, and the dimensions used afterwards don't depend on the way the path went.
+/// Hittables, which are tried in whatever order the scene is traversed, hash the ray instead.
+pub trait Sampler: Send {
+    fn samples_per_pixel(&self) -> u32;
+    /// Starts sample `index` of pixel `(x, y)`, and goes back to the first dimension.
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32);
+    /// A value in `[0.0, 1.0)`.
+    fn get_1d(&mut self) -> f64;
+    /// A point in `[0.0, 1.0)²`.
+    fn get_2d(&mut self) -> (f64, f64);
+    /// A fresh sampler with the same settings, for another thread.
+    fn clone_sampler(&self) -> Box<dyn Sampler>;
+}
+
+/// The sampler implementations, for choosing one by name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SamplerKind {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl SamplerKind {
+    pub fn build(&self, samples_per_pixel: u32, seed: u64) -> Box<dyn Sampler> {
+        match self {
+            SamplerKind::Independent => Box::new(IndependentSampler::new(samples_per_pixel, seed)),
+            SamplerKind::Stratified => {
+                // The closest grid to square; this can round the sample count up a little.
+                let x_strata = (samples_per_pixel as f64).sqrt().round().max(1.0) as u32;
+                Box::new(StratifiedSampler::new(x_strata, samples_per_pixel.div_ceil(x_strata), seed))
+            },
+            SamplerKind::Halton => Box::new(HaltonSampler::new(samples_per_pixel, seed)),
+            SamplerKind::Sobol => Box::new(SobolSampler::new(samples_per_pixel, seed)),
+        }
+    }
+}
+
+/// Finalizer from MurmurHash3; scrambles the bits of `v` thoroughly.
+pub(crate) fn mix_bits(mut v: u64) -> u64 {
+    v ^= v >> 33;
+    v = v.wrapping_mul(0xff51afd7ed558ccd);
+    v ^= v >> 33;
+    v = v.wrapping_mul(0xc4ceb9fe1a85ec53);
+    v ^= v >> 33;
+    v
+}
+
+pub(crate) fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0x9e3779b97f4a7c15, |h, v| mix_bits(h ^ v.wrapping_add(0x9e3779b97f4a7c15)))
+}
+
+/// A value in `[0.0, 1.0)` made from `ray` and a distance `t` along it, for random decisions taken while hitting
+/// things. Drawn from a `Sampler`, they would shift the dimensions of everything after them by however many
+/// objects the ray happened to be tested against.
+pub(crate) fn hash_ray(ray: &Ray, t: f64) -> f64 {
+    let values = [ray.origin.x, ray.origin.y, ray.origin.z, ray.direction.x, ray.direction.y, ray.direction.z, ray.time, t];
+    hash_to_float(hash(&values.map(f64::to_bits)))
+}
+
+/// Turns the top 53 bits of a hash into a float in `[0.0, 1.0)`.
+pub(crate) fn hash_to_float(h: u64) -> f64 {
+    (h >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+/// Element `i` of a random permutation of `0..length` chosen by `seed`, without building the permutation.
+/// From Kensler, "Correlated Multi-Jittered Sampling".
+pub(crate) fn permutation_element(mut i: u32, length: u32, seed: u32) -> u32 {
+    let mut w = length.wrapping_sub(1);
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < length {
+            return i.wrapping_add(seed) % length;
+        }
+    }
+}
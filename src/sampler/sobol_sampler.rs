@@ -0,0 +1,101 @@
+use super::{hash, mix_bits, permutation_element, Sampler, ONE_MINUS_EPSILON};
+
+/// Owen-scrambled Sobol points. Only the first two Sobol dimensions are used: every `get_1d` or `get_2d`
+/// call draws from them with its own shuffle of the sample index and its own scramble, which keeps the
+/// excellent 2D stratification without needing direction numbers for hundreds of dimensions.
+/// Works best with a power of two samples per pixel.
+#[derive(Clone)]
+pub struct SobolSampler {
+    samples_per_pixel: u32,
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl SobolSampler {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Self {
+        Self {
+            samples_per_pixel,
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn next_hash(&mut self) -> u64 {
+        let h = hash(&[self.seed, self.pixel.0 as u64, self.pixel.1 as u64, self.dimension as u64]);
+        self.dimension += 1;
+        h
+    }
+
+    fn shuffled_index(&self, h: u64) -> u32 {
+        permutation_element(self.index % self.samples_per_pixel, self.samples_per_pixel, h as u32)
+    }
+}
+
+/// First Sobol dimension, which is just the van der Corput sequence.
+fn sobol_0(a: u32) -> u32 {
+    a.reverse_bits()
+}
+
+/// Second Sobol dimension, from the primitive polynomial `x + 1`.
+fn sobol_1(mut a: u32) -> u32 {
+    let mut v: u32 = 1 << 31;
+    let mut result = 0;
+    while a != 0 {
+        if a & 1 != 0 {
+            result ^= v;
+        }
+        v ^= v >> 1;
+        a >>= 1;
+    }
+    result
+}
+
+/// Owen scrambling by hashing, from Laine and Karras, "Stratified Sampling for Stochastic Transparency".
+fn owen_scramble(mut v: u32, seed: u32) -> u32 {
+    v = v.reverse_bits();
+    v ^= v.wrapping_mul(0x3d20adea);
+    v = v.wrapping_add(seed);
+    v = v.wrapping_mul((seed >> 16) | 1);
+    v ^= v.wrapping_mul(0x05526c56);
+    v ^= v.wrapping_mul(0x53a22864);
+    v.reverse_bits()
+}
+
+fn to_float(v: u32) -> f64 {
+    (v as f64 / (1u64 << 32) as f64).min(ONE_MINUS_EPSILON)
+}
+
+impl Sampler for SobolSampler {
+    fn samples_per_pixel(&self) -> u32 {
+        self.samples_per_pixel
+    }
+
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = (x, y);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let h = self.next_hash();
+        let index = self.shuffled_index(h);
+        to_float(owen_scramble(sobol_0(index), (h >> 32) as u32))
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let h = self.next_hash();
+        let index = self.shuffled_index(h);
+        (
+            to_float(owen_scramble(sobol_0(index), (h >> 32) as u32)),
+            to_float(owen_scramble(sobol_1(index), mix_bits(h) as u32)),
+        )
+    }
+
+    fn clone_sampler(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
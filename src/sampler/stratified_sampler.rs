@@ -0,0 +1,72 @@
+use super::{hash, hash_to_float, permutation_element, Sampler};
+
+/// Jittered stratification: each dimension is split into one stratum per sample (a grid of `x_strata` by
+/// `y_strata` for 2D values) and every sample of a pixel lands in a different one. The strata are shuffled
+/// differently for every pixel and dimension so dimensions don't correlate.
+#[derive(Clone)]
+pub struct StratifiedSampler {
+    x_strata: u32,
+    y_strata: u32,
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl StratifiedSampler {
+    pub fn new(x_strata: u32, y_strata: u32, seed: u64) -> Self {
+        Self {
+            x_strata: x_strata.max(1),
+            y_strata: y_strata.max(1),
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn next_hash(&mut self) -> u64 {
+        let h = hash(&[self.seed, self.pixel.0 as u64, self.pixel.1 as u64, self.dimension as u64]);
+        self.dimension += 1;
+        h
+    }
+
+    fn jitter(&self, h: u64, axis: u64) -> f64 {
+        hash_to_float(hash(&[h, self.index as u64, axis]))
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn samples_per_pixel(&self) -> u32 {
+        self.x_strata * self.y_strata
+    }
+
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = (x, y);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let h = self.next_hash();
+        let count = self.samples_per_pixel();
+        let stratum = permutation_element(self.index % count, count, h as u32);
+        (stratum as f64 + self.jitter(h, 0)) / count as f64
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let h = self.next_hash();
+        let count = self.samples_per_pixel();
+        let stratum = permutation_element(self.index % count, count, h as u32);
+        let x = stratum % self.x_strata;
+        let y = stratum / self.x_strata;
+        (
+            (x as f64 + self.jitter(h, 0)) / self.x_strata as f64,
+            (y as f64 + self.jitter(h, 1)) / self.y_strata as f64,
+        )
+    }
+
+    fn clone_sampler(&self) -> Box<dyn Sampler> {
+        Box::new(self.clone())
+    }
+}
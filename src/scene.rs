@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use crate::animation::CameraPath;
+use crate::camera::PerspectiveCamera;
+use crate::hittable::Hittable;
+use crate::render::RenderSettings;
+use crate::vec3::{Color, Point3, Vec3};
+
+/// Everything needed to render a picture: the objects, how they are lit and how they are meant to be seen.
+pub struct Scene {
+    pub world: Arc<dyn Hittable>,
+    /// Color of rays that escape the world.
+    pub background: Color,
+    /// The emitting objects, which are also part of `world`.
+    // TODO: Sample these directly instead of waiting for paths to hit them.
+    pub lights: Vec<Arc<dyn Hittable>>,
+    /// Where the camera is over time. Still scenes have a single keyframe.
+    pub camera: CameraPath,
+    pub vup: Vec3,
+    /// The resolution, sample count and so on the scene is meant to be rendered with. The aspect ratio of the
+    /// camera comes from the resolution.
+    pub settings: RenderSettings,
+}
+
+impl Scene {
+    /// `world` under a light blue sky and without lights, seen by a still camera looking from `lookfrom` at
+    /// `lookat` with a vertical field of view of `vfov` degrees, in focus at `lookat` and without lens blur,
+    /// at the default settings.
+    pub fn new(world: Arc<dyn Hittable>, lookfrom: Point3, lookat: Point3, vfov: f64) -> Self {
+        let focus_dist = (&lookfrom - &lookat).length();
+        Self {
+            world,
+            background: Color { x: 0.7, y: 0.8, z: 1.0 },
+            lights: Vec::new(),
+            camera: CameraPath::fixed(lookfrom, lookat, vfov, 0.0, focus_dist),
+            vup: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+            settings: RenderSettings::default(),
+        }
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        self.settings.image_width as f64 / self.settings.image_height as f64
+    }
+
+    /// A perspective camera for the shutter interval `[time_start, time_end]`, held still where the scene's
+    /// camera is at `time_start`.
+    pub fn perspective_camera(&self, time_start: f64, time_end: f64) -> PerspectiveCamera {
+        let key = self.camera.at(time_start);
+        PerspectiveCamera::new(key.lookfrom, key.lookat, self.vup.clone(), key.vfov, self.aspect_ratio(), key.aperture, key.focus_dist, time_start, time_end)
+    }
+}
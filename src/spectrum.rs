@@ -0,0 +1,361 @@
+use std::sync::OnceLock;
+
+use crate::vec3::{Color, Vec3};
+
+/// The range of visible wavelengths that gets sampled, in nanometres.
+pub const LAMBDA_MIN: f64 = 380.0;
+pub const LAMBDA_MAX: f64 = 780.0;
+
+/// The CIE 1931 standard observer's color matching functions at `lambda` nanometres, as XYZ. Uses the multi-lobe
+/// Gaussian fit by Wyman, Sloan and Shirley (2013), which is within a few percent of the tabulated data.
+pub fn cie_xyz(lambda: f64) -> Vec3 {
+    // A Gaussian with different widths on either side of its peak.
+    let g = |mu: f64, sigma_below: f64, sigma_above: f64| {
+        let sigma = if lambda < mu { sigma_below } else { sigma_above };
+        (-0.5 * ((lambda - mu) / sigma).powi(2)).exp()
+    };
+    Vec3 {
+        x: 1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        y: 0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        z: 1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    }
+}
+
+/// Converts CIE XYZ to the linear RGB of the sRGB primaries and D65 white point that the rest of the renderer uses.
+pub fn xyz_to_rgb(xyz: &Vec3) -> Color {
+    Color {
+        x: 3.2404542 * xyz.x - 1.5371385 * xyz.y - 0.4985314 * xyz.z,
+        y: -0.9692660 * xyz.x + 1.8760108 * xyz.y + 0.0415560 * xyz.z,
+        z: 0.0556434 * xyz.x - 0.2040259 * xyz.y + 1.0572252 * xyz.z,
+    }
+}
+
+/// The luminance Y of a linear RGB color, which is how bright it looks.
+pub fn luminance(rgb: &Color) -> f64 {
+    0.2126729 * rgb.x + 0.7151522 * rgb.y + 0.0721750 * rgb.z
+}
+
+/// The color of a black body glowing at `temperature` kelvin, scaled to a luminance of 1: about 1900 K for a
+/// candle, 2700 K for an incandescent bulb and 6500 K for overcast daylight.
+pub fn blackbody(temperature: f64) -> Color {
+    // Planck's law, with the constants of the SI.
+    const H: f64 = 6.62607015e-34;
+    const C: f64 = 299792458.0;
+    const K: f64 = 1.380649e-23;
+    let radiance = |lambda: f64| {
+        let lambda = lambda * 1e-9;
+        2.0 * H * C * C / lambda.powi(5) / ((H * C / (lambda * K * temperature)).exp() - 1.0)
+    };
+    let mut xyz = Vec3::new();
+    let mut lambda = LAMBDA_MIN;
+    while lambda <= LAMBDA_MAX {
+        xyz += radiance(lambda) * cie_xyz(lambda);
+        lambda += 1.0;
+    }
+    // The reddest and bluest are a little out of the sRGB gamut.
+    let rgb = xyz_to_rgb(&(&xyz / xyz.y));
+    Color { x: rgb.x.max(0.0), y: rgb.y.max(0.0), z: rgb.z.max(0.0) }
+}
+
+/// Picks a wavelength, uniformly over the visible range, from `u` in [0, 1).
+pub(crate) fn sample_wavelength(u: f64) -> f64 {
+    LAMBDA_MIN + u * (LAMBDA_MAX - LAMBDA_MIN)
+}
+
+/// The wavelengths a path carries, which decide what the colors along it mean.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Wavelengths {
+    /// Colors are linear RGB.
+    #[default]
+    Rgb,
+    /// Colors are linear RGB, weighted for the single wavelength, in nanometres, that the path was narrowed down to.
+    Single(f64),
+    /// Colors are the radiance at these wavelengths, in nanometres, the first being the hero wavelength. Once
+    /// something wavelength dependent narrows the path down to the hero alone, `hero_only` is set and the others
+    /// carry nothing.
+    Spectral { lambda: [f64; 3], hero_only: bool },
+}
+
+impl Wavelengths {
+    /// A hero wavelength picked by `u`, and two more spread evenly from it over the visible range, so that together
+    /// they cover it better than three independent picks would.
+    pub fn sample_spectral(u: f64) -> Self {
+        let hero = sample_wavelength(u);
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / 3.0;
+        let rotate = |i: f64| {
+            let lambda = hero + i * step;
+            if lambda >= LAMBDA_MAX { lambda - (LAMBDA_MAX - LAMBDA_MIN) } else { lambda }
+        };
+        Wavelengths::Spectral { lambda: [hero, rotate(1.0), rotate(2.0)], hero_only: false }
+    }
+
+    /// Narrows the path down to a single wavelength, for something that can only follow one at a time, like a
+    /// dispersive `Dielectric`. Returns the narrowed wavelengths and the one to use. A path that doesn't carry any
+    /// wavelengths yet gets the one picked by `u`.
+    pub fn narrow(self, u: f64) -> (Self, f64) {
+        match self {
+            Wavelengths::Rgb => {
+                let lambda = sample_wavelength(u);
+                (Wavelengths::Single(lambda), lambda)
+            }
+            Wavelengths::Single(lambda) => (self, lambda),
+            Wavelengths::Spectral { lambda, .. } => (Wavelengths::Spectral { lambda, hero_only: true }, lambda[0]),
+        }
+    }
+
+    /// What the colors of a path that went from `self` to `narrowed` are weighted by, so that on average it carries
+    /// as much light as it did before.
+    pub(crate) fn narrowing_weight(&self, narrowed: &Wavelengths) -> Color {
+        match (self, narrowed) {
+            (Wavelengths::Rgb, Wavelengths::Single(lambda)) => wavelength_weight(*lambda),
+            (Wavelengths::Spectral { hero_only: false, .. }, Wavelengths::Spectral { hero_only: true, .. }) => Color { x: 3.0, y: 0.0, z: 0.0 },
+            _ => Color { x: 1.0, y: 1.0, z: 1.0 },
+        }
+    }
+
+    /// An RGB reflectance or attenuation, like materials return, as the path carries colors.
+    pub fn reflectance(&self, rgb: &Color) -> Color {
+        match self {
+            Wavelengths::Spectral { lambda, .. } => Color { x: rgb_to_spectrum(rgb, lambda[0]), y: rgb_to_spectrum(rgb, lambda[1]), z: rgb_to_spectrum(rgb, lambda[2]) },
+            _ => rgb.clone(),
+        }
+    }
+
+    /// An RGB emitted radiance, like lights and the background give, as the path carries colors. White light has
+    /// the spectrum of daylight, D65, so that it comes out white again.
+    pub fn illuminant(&self, rgb: &Color) -> Color {
+        match self {
+            Wavelengths::Spectral { lambda, .. } => {
+                let scale = |lambda: f64| d65(lambda) / d65_luminance();
+                Color { x: rgb_to_spectrum(rgb, lambda[0]) * scale(lambda[0]), y: rgb_to_spectrum(rgb, lambda[1]) * scale(lambda[1]), z: rgb_to_spectrum(rgb, lambda[2]) * scale(lambda[2]) }
+            }
+            _ => rgb.clone(),
+        }
+    }
+
+    /// The CIE XYZ color of `radiance` carried at spectral wavelengths, as an estimate over the whole visible range.
+    pub fn to_xyz(&self, radiance: &Color) -> Vec3 {
+        let Wavelengths::Spectral { lambda, .. } = self else {
+            panic!("Only spectral paths convert to XYZ!");
+        };
+        let mut xyz = Vec3::new();
+        for (lambda, radiance) in lambda.iter().zip(radiance) {
+            // Divided by the density of picking each wavelength, averaged over the three.
+            xyz += radiance * (LAMBDA_MAX - LAMBDA_MIN) / 3.0 * cie_xyz(*lambda);
+        }
+        xyz
+    }
+}
+
+// Smits' basis spectra (1999) at 10 wavelengths evenly spaced over [380, 720] nm: smooth, never negative, and adding
+// up to any RGB reflectance.
+const SMITS_LAMBDA_MIN: f64 = 380.0;
+const SMITS_LAMBDA_MAX: f64 = 720.0;
+const SMITS_WHITE: [f64; 10] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const SMITS_CYAN: [f64; 10] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const SMITS_MAGENTA: [f64; 10] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const SMITS_YELLOW: [f64; 10] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const SMITS_RED: [f64; 10] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const SMITS_GREEN: [f64; 10] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const SMITS_BLUE: [f64; 10] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
+// Linear interpolation in a table of values evenly spaced over [min, max], held constant outside it.
+fn interpolate(table: &[f64], min: f64, max: f64, lambda: f64) -> f64 {
+    let t = (lambda - min) / (max - min) * (table.len() - 1) as f64;
+    if t <= 0.0 {
+        return table[0];
+    }
+    let i = t as usize;
+    if i >= table.len() - 1 {
+        return table[table.len() - 1];
+    }
+    let f = t - i as f64;
+    table[i] * (1.0 - f) + table[i + 1] * f
+}
+
+/// A smooth spectrum with the RGB color `rgb`, evaluated at `lambda` nanometres.
+pub fn rgb_to_spectrum(rgb: &Color, lambda: f64) -> f64 {
+    let basis = |table: &[f64; 10]| interpolate(table, SMITS_LAMBDA_MIN, SMITS_LAMBDA_MAX, lambda);
+    let (r, g, b) = (rgb.x, rgb.y, rgb.z);
+    // White up to the smallest component, then the secondary color up to the middle one, then the primary.
+    if r <= g && r <= b {
+        r * basis(&SMITS_WHITE) + if g <= b { (g - r) * basis(&SMITS_CYAN) + (b - g) * basis(&SMITS_BLUE) } else { (b - r) * basis(&SMITS_CYAN) + (g - b) * basis(&SMITS_GREEN) }
+    } else if g <= r && g <= b {
+        g * basis(&SMITS_WHITE) + if r <= b { (r - g) * basis(&SMITS_MAGENTA) + (b - r) * basis(&SMITS_BLUE) } else { (b - g) * basis(&SMITS_MAGENTA) + (r - b) * basis(&SMITS_RED) }
+    } else {
+        b * basis(&SMITS_WHITE) + if r <= g { (r - b) * basis(&SMITS_YELLOW) + (g - r) * basis(&SMITS_GREEN) } else { (g - b) * basis(&SMITS_YELLOW) + (r - g) * basis(&SMITS_RED) }
+    }
+}
+
+// The relative spectral power of CIE standard illuminant D65, every 10 nm from 380 to 780 nm.
+const D65: [f64; 41] = [
+    49.98, 54.65, 82.75, 91.49, 93.43, 86.68, 104.86, 117.01, 117.81, 114.86, 115.92, 108.81, 109.35, 107.80, 104.79, 107.69, 104.41, 104.05, 100.00, 96.33, 95.79,
+    88.69, 90.01, 89.60, 87.70, 83.29, 83.70, 80.03, 80.21, 82.28, 78.28, 69.72, 71.61, 74.35, 61.60, 69.89, 75.09, 63.59, 46.42, 66.81, 63.38,
+];
+
+fn d65(lambda: f64) -> f64 {
+    interpolate(&D65, 380.0, 780.0, lambda)
+}
+
+// The luminance Y of `d65`, for scaling it to a luminance of 1.
+fn d65_luminance() -> f64 {
+    static LUMINANCE: OnceLock<f64> = OnceLock::new();
+    *LUMINANCE.get_or_init(|| {
+        const STEPS: usize = 4000;
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / STEPS as f64;
+        (0..STEPS).map(|i| LAMBDA_MIN + (i as f64 + 0.5) * step).map(|lambda| d65(lambda) * cie_xyz(lambda).y * step).sum()
+    })
+}
+
+// RGB of a single wavelength, before normalizing. Pure spectral colors are outside the RGB gamut, so they are
+// clipped to it: that desaturates rainbows slightly, but keeps every path's color positive.
+fn clipped_rgb(lambda: f64) -> Color {
+    let rgb = xyz_to_rgb(&cie_xyz(lambda));
+    Color { x: rgb.x.max(0.0), y: rgb.y.max(0.0), z: rgb.z.max(0.0) }
+}
+
+/// The color a path takes on when it is narrowed down to the single wavelength `lambda` picked by
+/// `sample_wavelength`. Over all wavelengths, this averages to white.
+pub fn wavelength_weight(lambda: f64) -> Color {
+    static MEAN: OnceLock<Color> = OnceLock::new();
+    let mean = MEAN.get_or_init(|| {
+        const STEPS: usize = 4000;
+        let mut sum = Color::new();
+        for i in 0..STEPS {
+            sum += clipped_rgb(sample_wavelength((i as f64 + 0.5) / STEPS as f64));
+        }
+        sum / STEPS as f64
+    });
+    let rgb = clipped_rgb(lambda);
+    Color { x: rgb.x / mean.x, y: rgb.y / mean.y, z: rgb.z / mean.z }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn luminance_peaks_in_the_green() {
+        let peak = (LAMBDA_MIN as u32..LAMBDA_MAX as u32).max_by(|a, b| cie_xyz(*a as f64).y.total_cmp(&cie_xyz(*b as f64).y)).unwrap();
+        assert!((550..=560).contains(&peak), "Luminance peaks at {} nm", peak);
+        assert!((cie_xyz(555.0).y - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn wavelengths_look_like_their_colors() {
+        for (lambda, channel) in [(450.0, 2), (530.0, 1), (640.0, 0)] {
+            let weight = wavelength_weight(lambda);
+            let channels: Vec<f64> = weight.into_iter().collect();
+            let brightest = (0..3).max_by(|a, b| channels[*a].total_cmp(&channels[*b])).unwrap();
+            assert_eq!(brightest, channel, "{} nm looks like ({})", lambda, weight);
+        }
+    }
+
+    #[test]
+    fn sampled_weights_average_to_white() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let count = 200_000;
+        let mut sum = Color::new();
+        for _ in 0..count {
+            let weight = wavelength_weight(sample_wavelength(rng.gen()));
+            assert!(weight.into_iter().all(|channel| channel >= 0.0));
+            sum += weight;
+        }
+        let mean = sum / count as f64;
+        assert!(mean.into_iter().all(|channel| (channel - 1.0).abs() < 0.02), "Weights average to ({})", mean);
+    }
+
+    #[test]
+    fn equal_energy_white_is_close_to_rgb_white() {
+        // Illuminant E is a little pinker than D65, but nowhere near a primary.
+        let mut xyz = Vec3::new();
+        for lambda in LAMBDA_MIN as u32..LAMBDA_MAX as u32 {
+            xyz += cie_xyz(lambda as f64 + 0.5);
+        }
+        let rgb = xyz_to_rgb(&(&xyz / xyz.y));
+        assert!(rgb.into_iter().all(|channel| (channel - 1.0).abs() < 0.25), "Illuminant E is ({})", rgb);
+    }
+
+    // The RGB color of light with the spectrum of `rgb` as a reflectance under white light, integrated finely.
+    fn round_trip(rgb: &Color) -> Color {
+        const STEPS: usize = 4000;
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / STEPS as f64;
+        let mut xyz = Vec3::new();
+        for i in 0..STEPS {
+            let lambda = LAMBDA_MIN + (i as f64 + 0.5) * step;
+            xyz += rgb_to_spectrum(rgb, lambda) * d65(lambda) / d65_luminance() * step * cie_xyz(lambda);
+        }
+        xyz_to_rgb(&xyz)
+    }
+
+    #[test]
+    fn upsampled_colors_round_trip() {
+        // Everyday colors come back closely, pure primaries less so, since no smooth spectrum quite reaches them.
+        for (rgb, tolerance) in [
+            (Color { x: 1.0, y: 1.0, z: 1.0 }, 0.01),
+            (Color { x: 0.5, y: 0.5, z: 0.5 }, 0.01),
+            (Color { x: 0.7, y: 0.6, z: 0.5 }, 0.02),
+            (Color { x: 0.5, y: 0.2, z: 0.1 }, 0.03),
+            (Color { x: 0.12, y: 0.45, z: 0.15 }, 0.04),
+            (Color { x: 1.0, y: 0.0, z: 0.0 }, 0.1),
+            (Color { x: 0.0, y: 1.0, z: 0.0 }, 0.1),
+            (Color { x: 0.0, y: 0.0, z: 1.0 }, 0.1),
+        ] {
+            let back = round_trip(&rgb);
+            assert!((&back - &rgb).into_iter().all(|difference| difference.abs() < tolerance), "({}) came back as ({})", rgb, back);
+        }
+    }
+
+    #[test]
+    fn sampled_wavelengths_are_spread_over_the_visible_range() {
+        for i in 0..100 {
+            let Wavelengths::Spectral { lambda, hero_only: false } = Wavelengths::sample_spectral(i as f64 / 100.0) else {
+                panic!("Not spectral!");
+            };
+            let mut sorted = lambda;
+            sorted.sort_by(f64::total_cmp);
+            assert!(sorted.iter().all(|lambda| (LAMBDA_MIN..LAMBDA_MAX).contains(lambda)), "Wavelengths {:?}", lambda);
+            assert!((sorted[1] - sorted[0] - (LAMBDA_MAX - LAMBDA_MIN) / 3.0).abs() < 1e-9 && (sorted[2] - sorted[1] - (LAMBDA_MAX - LAMBDA_MIN) / 3.0).abs() < 1e-9, "Wavelengths {:?}", lambda);
+        }
+    }
+
+    #[test]
+    fn white_light_comes_out_white() {
+        let white = Color { x: 1.0, y: 1.0, z: 1.0 };
+        let count = 10_000;
+        let (mut full, mut narrowed) = (Vec3::new(), Vec3::new());
+        for i in 0..count {
+            let wavelengths = Wavelengths::sample_spectral((i as f64 + 0.5) / count as f64);
+            full += wavelengths.to_xyz(&wavelengths.illuminant(&white));
+            // Narrowing down to the hero wavelength alone must not change the average either.
+            let (hero_only, _) = wavelengths.narrow(0.0);
+            narrowed += wavelengths.to_xyz(&(wavelengths.narrowing_weight(&hero_only) * hero_only.illuminant(&white)));
+        }
+        for xyz in [full, narrowed] {
+            let rgb = xyz_to_rgb(&(xyz / count as f64));
+            assert!(rgb.into_iter().all(|channel| (channel - 1.0).abs() < 0.01), "White came out as ({})", rgb);
+        }
+    }
+
+    #[test]
+    fn blackbodies_get_bluer_as_they_heat_up() {
+        let (candle, bulb, daylight) = (blackbody(1900.0), blackbody(2700.0), blackbody(6500.0));
+        assert!(candle.z / candle.x < bulb.z / bulb.x && bulb.z / bulb.x < daylight.z / daylight.x);
+        // Daylight, near D65, is nearly white.
+        assert!(daylight.into_iter().all(|channel| (channel - 1.0).abs() < 0.1), "Daylight is ({})", daylight);
+        for color in [candle, bulb, daylight] {
+            assert!((luminance(&color) - 1.0).abs() < 0.01, "Luminance of ({}) is {}", color, luminance(&color));
+        }
+    }
+
+    #[test]
+    fn rgb_paths_are_left_alone() {
+        let rgb = Color { x: 0.2, y: 0.5, z: 0.9 };
+        for wavelengths in [Wavelengths::Rgb, Wavelengths::Single(500.0)] {
+            assert_eq!(wavelengths.reflectance(&rgb).into_iter().collect::<Vec<_>>(), rgb.into_iter().collect::<Vec<_>>());
+            assert_eq!(wavelengths.illuminant(&rgb).into_iter().collect::<Vec<_>>(), rgb.into_iter().collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::cell::Cell;
+use std::fmt;
+use std::ops::AddAssign;
+use std::time::Duration;
+
+use crate::util::json_string;
+
+/// Work done while rendering. Each render thread counts into its own copy, so counting costs no synchronization.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Counters {
+    /// Rays traced through the scene, from the camera and scattered.
+    pub rays: u64,
+    /// Rays traced towards lights to test their visibility.
+    // TODO: Nothing traces these until lights are sampled directly, see `Scene::lights`.
+    pub shadow_rays: u64,
+    pub bvh_node_visits: u64,
+    /// Ray intersection tests against spheres, triangles and rectangles.
+    pub primitive_tests: u64,
+}
+
+impl AddAssign for Counters {
+    fn add_assign(&mut self, other: Self) {
+        self.rays += other.rays;
+        self.shadow_rays += other.shadow_rays;
+        self.bvh_node_visits += other.bvh_node_visits;
+        self.primitive_tests += other.primitive_tests;
+    }
+}
+
+thread_local! {
+    static COUNTERS: Cell<Counters> = const { Cell::new(Counters { rays: 0, shadow_rays: 0, bvh_node_visits: 0, primitive_tests: 0 }) };
+}
+
+fn count(update: impl FnOnce(&mut Counters)) {
+    COUNTERS.with(|counters| {
+        let mut value = counters.get();
+        update(&mut value);
+        counters.set(value);
+    });
+}
+
+pub(crate) fn count_ray() {
+    count(|counters| counters.rays += 1);
+}
+
+pub(crate) fn count_bvh_node_visit() {
+    count(|counters| counters.bvh_node_visits += 1);
+}
+
+pub(crate) fn count_primitive_test() {
+    count(|counters| counters.primitive_tests += 1);
+}
+
+/// Returns what this thread counted since the last call, and starts over.
+pub(crate) fn take_counters() -> Counters {
+    COUNTERS.with(|counters| counters.take())
+}
+
+/// Summary of a finished render.
+#[derive(Clone, Debug)]
+pub struct RenderStats {
+    pub image_width: u32,
+    pub image_height: u32,
+    pub samples_per_pixel: u32,
+    pub thread_count: u32,
+    /// Camera samples taken, over the whole image.
+    pub samples: u64,
+    pub counters: Counters,
+    pub elapsed: Duration,
+}
+
+impl RenderStats {
+    pub fn samples_per_second(&self) -> f64 {
+        per_second(self.samples, self.elapsed)
+    }
+
+    pub fn rays_per_second(&self) -> f64 {
+        per_second(self.counters.rays, self.elapsed)
+    }
+
+    pub fn bvh_node_visits_per_ray(&self) -> f64 {
+        per_ray(self.counters.bvh_node_visits, self.counters.rays)
+    }
+
+    pub fn primitive_tests_per_ray(&self) -> f64 {
+        per_ray(self.counters.primitive_tests, self.counters.rays)
+    }
+
+    /// The stats as a single line JSON object, for collecting from scripts.
+    pub fn to_json(&self) -> String {
+        let fields = [
+            ("image_width", self.image_width.to_string()),
+            ("image_height", self.image_height.to_string()),
+            ("samples_per_pixel", self.samples_per_pixel.to_string()),
+            ("thread_count", self.thread_count.to_string()),
+            ("samples", self.samples.to_string()),
+            ("rays", self.counters.rays.to_string()),
+            ("shadow_rays", self.counters.shadow_rays.to_string()),
+            ("bvh_node_visits", self.counters.bvh_node_visits.to_string()),
+            ("primitive_tests", self.counters.primitive_tests.to_string()),
+            ("elapsed_seconds", self.elapsed.as_secs_f64().to_string()),
+            ("samples_per_second", self.samples_per_second().to_string()),
+            ("rays_per_second", self.rays_per_second().to_string()),
+            ("bvh_node_visits_per_ray", self.bvh_node_visits_per_ray().to_string()),
+            ("primitive_tests_per_ray", self.primitive_tests_per_ray().to_string()),
+        ];
+        let fields: Vec<String> = fields.iter().map(|(key, value)| format!("{}:{}", json_string(key), value)).collect();
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+impl fmt::Display for RenderStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Rendered {}x{} at {} samples per pixel in {} on {} threads.", self.image_width, self.image_height, self.samples_per_pixel, format_duration(self.elapsed), self.thread_count)?;
+        writeln!(f, "  Samples:           {:>14} ({}/s)", self.samples, format_rate(self.samples_per_second()))?;
+        writeln!(f, "  Rays:              {:>14} ({}/s)", self.counters.rays, format_rate(self.rays_per_second()))?;
+        writeln!(f, "  Shadow rays:       {:>14}", self.counters.shadow_rays)?;
+        writeln!(f, "  BVH node visits:   {:>14} ({:.1} per ray)", self.counters.bvh_node_visits, self.bvh_node_visits_per_ray())?;
+        write!(f, "  Primitive tests:   {:>14} ({:.1} per ray)", self.counters.primitive_tests, self.primitive_tests_per_ray())
+    }
+}
+
+pub(crate) fn per_second(count: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() { 0.0 } else { count as f64 / elapsed.as_secs_f64() }
+}
+
+fn per_ray(count: u64, rays: u64) -> f64 {
+    if rays == 0 { 0.0 } else { count as f64 / rays as f64 }
+}
+
+/// `h:mm:ss`, or `m:ss` under an hour.
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// A rate with a metric suffix, like `12.3M`.
+pub(crate) fn format_rate(rate: f64) -> String {
+    if rate >= 1e9 {
+        format!("{:.1}G", rate / 1e9)
+    } else if rate >= 1e6 {
+        format!("{:.1}M", rate / 1e6)
+    } else if rate >= 1e3 {
+        format!("{:.1}k", rate / 1e3)
+    } else {
+        format!("{:.0}", rate)
+    }
+}
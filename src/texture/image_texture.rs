@@ -1,17 +1,24 @@
 use super::Texture;
 use crate::{vec3::Color, vec3::Point3};
 
-// assume 24 bit depth
-const BYTES_PER_PIXEL: usize = 3;
+// assume 24 or 32 bit depth, in blue, green, red and maybe alpha order
 pub struct ImageTexture {
     data: Vec<u8>,
     width: usize,
     height: usize,
+    bytes_per_pixel: usize,
     bytes_per_scanline: usize,
+    // Whether the fourth byte of each pixel is alpha, rather than padding.
+    has_alpha: bool,
 }
 
+// Compression method that follows a BITMAPINFOHEADER with color masks, alpha included.
+const BI_ALPHABITFIELDS: u32 = 6;
+// Size of the BITMAPV3INFOHEADER, the first one with room for an alpha mask, which may still be 0.
+const V3_HEADER_SIZE: u32 = 56;
+
 impl ImageTexture {
-    pub fn from_bmp_data(bmp_data: &Vec<u8>) -> Self {
+    pub fn from_bmp_data(bmp_data: &[u8]) -> Self {
         let data_position = u32::from_le_bytes([
             bmp_data[0x0A],
             bmp_data[0x0B],
@@ -31,17 +38,29 @@ impl ImageTexture {
             bmp_data[0x18],
             bmp_data[0x19],
         ]) as usize;
+        let bits_per_pixel = u16::from_le_bytes([bmp_data[0x1C], bmp_data[0x1D]]) as usize;
+        let bytes_per_pixel = if bits_per_pixel == 32 { 4 } else { 3 };
+        // The fourth byte is only alpha if the header says so; plain BI_RGB images leave it as padding, often 0.
+        let header_size = u32::from_le_bytes([bmp_data[0x0E], bmp_data[0x0F], bmp_data[0x10], bmp_data[0x11]]);
+        let compression = u32::from_le_bytes([bmp_data[0x1E], bmp_data[0x1F], bmp_data[0x20], bmp_data[0x21]]);
+        // Exporters write 32 bit images without alpha with a zero alpha mask, so its presence alone isn't enough.
+        let alpha_mask = if header_size >= V3_HEADER_SIZE || compression == BI_ALPHABITFIELDS {
+            u32::from_le_bytes([bmp_data[0x42], bmp_data[0x43], bmp_data[0x44], bmp_data[0x45]])
+        } else {
+            0
+        };
+        let has_alpha = bytes_per_pixel == 4 && alpha_mask != 0;
         Self {
             data: bmp_data[(data_position as usize)..bmp_data.len()].to_vec(),
             height,
             width,
-            bytes_per_scanline: BYTES_PER_PIXEL * width,
+            bytes_per_pixel,
+            bytes_per_scanline: bytes_per_pixel * width,
+            has_alpha,
         }
     }
-}
 
-impl Texture for ImageTexture {
-    fn value(&self, u: f64, v: f64, _: &Point3) -> Color {
+    fn pixel(&self, u: f64, v: f64) -> usize {
         let u = u.clamp(0.0, 1.0);
         // This is a deviation from the book, where v gets flipped.
         // This is probably because the BMP loader loads in stuff upside down.
@@ -52,12 +71,96 @@ impl Texture for ImageTexture {
 
         if i >= self.width { i = self.width - 1 };
         if j >= self.height { j = self.height - 1 };
+        j * self.bytes_per_scanline + i * self.bytes_per_pixel
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _: &Point3) -> Color {
         let color_scale = 1.0 / 255.0;
-        let pixel = j * self.bytes_per_scanline + i * BYTES_PER_PIXEL;
+        let pixel = self.pixel(u, v);
         Color {
             x: color_scale * *self.data.get(pixel + 2).unwrap() as f64,
             y: color_scale * *self.data.get(pixel + 1).unwrap() as f64,
             z: color_scale * *self.data.get(pixel).unwrap() as f64,
         }
     }
+
+    fn alpha(&self, u: f64, v: f64, p: &Point3) -> f64 {
+        if !self.has_alpha {
+            return self.scalar(u, v, p);
+        }
+        *self.data.get(self.pixel(u, v) + 3).unwrap() as f64 / 255.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A BMP with just enough of a BITMAPINFOHEADER for `from_bmp_data`, and the given pixels, uncompressed.
+    fn bmp(width: u32, bits_per_pixel: u16, pixels: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; 0x36];
+        data[0x0A..0x0E].copy_from_slice(&0x36_u32.to_le_bytes());
+        data[0x0E..0x12].copy_from_slice(&40_u32.to_le_bytes());
+        data[0x12..0x16].copy_from_slice(&width.to_le_bytes());
+        data[0x16..0x1A].copy_from_slice(&1_u32.to_le_bytes());
+        data[0x1C..0x1E].copy_from_slice(&bits_per_pixel.to_le_bytes());
+        data.extend_from_slice(pixels);
+        data
+    }
+
+    // A 32 bit BMP with BI_ALPHABITFIELDS masks declaring the fourth byte alpha.
+    fn bmp_with_alpha(width: u32, pixels: &[u8]) -> Vec<u8> {
+        let mut data = bmp(width, 32, &[]);
+        data[0x0A..0x0E].copy_from_slice(&0x46_u32.to_le_bytes());
+        data[0x1E..0x22].copy_from_slice(&BI_ALPHABITFIELDS.to_le_bytes());
+        for mask in [0x00FF0000_u32, 0x0000FF00, 0x000000FF, 0xFF000000] {
+            data.extend_from_slice(&mask.to_le_bytes());
+        }
+        data.extend_from_slice(pixels);
+        data
+    }
+
+    #[test]
+    fn reads_the_alpha_channel_of_32_bit_images() {
+        // Blue, green, red and alpha.
+        let image = ImageTexture::from_bmp_data(&bmp_with_alpha(2, &[0, 0, 255, 255, 255, 0, 0, 0]));
+        let p = Point3::new();
+        assert_eq!(image.value(0.25, 0.5, &p).x, 1.0);
+        assert_eq!(image.value(0.75, 0.5, &p).z, 1.0);
+        assert_eq!(image.alpha(0.25, 0.5, &p), 1.0);
+        assert_eq!(image.alpha(0.75, 0.5, &p), 0.0);
+    }
+
+    #[test]
+    fn images_without_alpha_mask_by_brightness() {
+        let image = ImageTexture::from_bmp_data(&bmp(2, 24, &[255, 255, 255, 0, 0, 0]));
+        let p = Point3::new();
+        assert_eq!(image.alpha(0.25, 0.5, &p), 1.0);
+        assert_eq!(image.alpha(0.75, 0.5, &p), 0.0);
+    }
+
+    #[test]
+    fn ignores_the_fourth_byte_unless_the_header_declares_alpha() {
+        // BI_RGB, where the fourth byte is padding.
+        let image = ImageTexture::from_bmp_data(&bmp(1, 32, &[255, 255, 255, 0]));
+        let p = Point3::new();
+        assert_eq!(image.value(0.5, 0.5, &p).x, 1.0);
+        assert_eq!(image.alpha(0.5, 0.5, &p), 1.0);
+    }
+
+    #[test]
+    fn ignores_the_fourth_byte_of_v5_images_with_a_zero_alpha_mask() {
+        // A BITMAPV5HEADER with BI_BITFIELDS masks for blue, green and red only, the way GIMP writes XRGB images.
+        let mut data = bmp(1, 32, &[]);
+        data[0x0A..0x0E].copy_from_slice(&0x8A_u32.to_le_bytes());
+        data[0x0E..0x12].copy_from_slice(&124_u32.to_le_bytes());
+        data[0x1E..0x22].copy_from_slice(&3_u32.to_le_bytes());
+        data.resize(0x8A, 0);
+        data[0x36..0x42].copy_from_slice(&[0, 0, 0xFF, 0, 0, 0xFF, 0, 0, 0xFF, 0, 0, 0]);
+        data.extend_from_slice(&[255, 255, 255, 0]);
+        let image = ImageTexture::from_bmp_data(&data);
+        assert_eq!(image.alpha(0.5, 0.5, &Point3::new()), 1.0);
+    }
 }
@@ -12,4 +12,17 @@ use crate::{vec3::Color, vec3::Point3};
 
 pub trait Texture: Send + Sync {
     fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+
+    /// The texture as a single number, for parameters like roughness: the mean of its channels, so grayscale maps
+    /// work as they are.
+    fn scalar(&self, u: f64, v: f64, p: &Point3) -> f64 {
+        let color = self.value(u, v, p);
+        (color.x + color.y + color.z) / 3.0
+    }
+
+    /// How opaque the texture is, for cutting out surfaces: its alpha channel if it has one, or else `scalar`, so
+    /// black and white masks work too.
+    fn alpha(&self, u: f64, v: f64, p: &Point3) -> f64 {
+        self.scalar(u, v, p)
+    }
 }
@@ -1,3 +1,5 @@
+use rand::Rng;
+
 use super::{Texture, perlin::Perlin};
 use crate::{vec3::Color, vec3::Point3};
 
@@ -7,9 +9,9 @@ pub struct NoiseTexture {
 }
 
 impl NoiseTexture {
-    pub fn new(scale: f64) -> Self {
+    pub fn new(scale: f64, rng: &mut impl Rng) -> Self {
         Self {
-            noise: Perlin::new(),
+            noise: Perlin::new(rng),
             scale,
         }
     }
@@ -1,3 +1,5 @@
+use rand::Rng;
+
 use crate::vec3::{Vec3, Point3};
 
 const POINT_COUNT: usize = 256;
@@ -10,16 +12,16 @@ pub struct Perlin {
 }
 
 impl Perlin {
-    pub fn new() -> Self {
+    pub fn new(rng: &mut impl Rng) -> Self {
         let mut ranvec = Vec::with_capacity(POINT_COUNT);
         for _ in 0..POINT_COUNT {
-            ranvec.push(Vec3::random_in_range(-1.0, 1.0).unit_vector());
+            ranvec.push(Vec3::random_in_range(rng, -1.0, 1.0).unit_vector());
         }
         Self {
             ranvec,
-            perm_x: Self::generate_perm(),
-            perm_y: Self::generate_perm(),
-            perm_z: Self::generate_perm(),
+            perm_x: Self::generate_perm(rng),
+            perm_y: Self::generate_perm(rng),
+            perm_z: Self::generate_perm(rng),
         }
     }
 
@@ -36,6 +38,8 @@ impl Perlin {
         accum.abs()
     }
 
+    // The loop variables are offsets as well as indices.
+    #[allow(clippy::needless_range_loop)]
     pub fn noise(&self, point: &Point3) -> f64 {
         let u = point.x - point.x.floor();
         let v = point.y - point.y.floor();
@@ -50,10 +54,9 @@ impl Perlin {
                     c[di][dj][dk] = self
                         .ranvec
                         .get(
-                            (self.perm_x.get(((i + di as i32) & 255) as usize).unwrap()
+                            self.perm_x.get(((i + di as i32) & 255) as usize).unwrap()
                                 ^ self.perm_y.get(((j + dj as i32) & 255) as usize).unwrap()
-                                ^ self.perm_z.get(((k + dk as i32) & 255) as usize).unwrap())
-                                as usize,
+                                ^ self.perm_z.get(((k + dk as i32) & 255) as usize).unwrap(),
                         )
                         .unwrap().clone();
                 }
@@ -62,6 +65,7 @@ impl Perlin {
         Self::trilinear_interpolate(c, u, v, w)
     }
 
+    #[allow(clippy::needless_range_loop)]
     fn trilinear_interpolate(c: [[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
         let uu = u * u * (3.0 - 2.0 * u);
         let vv = v * v * (3.0 - 2.0 * v);
@@ -84,15 +88,15 @@ impl Perlin {
         accum
     }
 
-    fn generate_perm() -> Vec<usize> {
-        let mut p = (0..POINT_COUNT).collect();
-        Self::permute(&mut p, POINT_COUNT);
+    fn generate_perm(rng: &mut impl Rng) -> Vec<usize> {
+        let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+        Self::permute(rng, &mut p, POINT_COUNT);
         p
     }
 
-    fn permute(p: &mut Vec<usize>, n: usize) {
+    fn permute(rng: &mut impl Rng, p: &mut [usize], n: usize) {
         for i in (1..n).rev() {
-            p.swap(i, rand::random::<usize>() % i);
+            p.swap(i, rng.gen::<usize>() % i);
         }
     }
 }
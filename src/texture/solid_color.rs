@@ -11,6 +11,11 @@ impl SolidColor {
             color_value,
         }
     }
+
+    /// A gray, for parameters read with `Texture::scalar`.
+    pub fn from_value(value: f64) -> Self {
+        Self::from_color(Color { x: value, y: value, z: value })
+    }
 }
 
 impl Texture for SolidColor {
@@ -1,4 +1,5 @@
 use auto_ops::{impl_op_ex, impl_op_ex_commutative};
+use rand::Rng;
 use std::fmt;
 
 pub type Point3 = Vec3;
@@ -62,49 +63,56 @@ impl Vec3 {
         self / self.length()
     }
 
-    pub fn random() -> Vec3 {
+    pub fn random(rng: &mut impl Rng) -> Vec3 {
         Vec3 {
-            x: rand::random::<f64>(),
-            y: rand::random::<f64>(),
-            z: rand::random::<f64>(),
+            x: rng.gen::<f64>(),
+            y: rng.gen::<f64>(),
+            z: rng.gen::<f64>(),
         }
     }
 
-    pub fn random_in_range(min: f64, max: f64) -> Vec3 {
+    pub fn random_in_range(rng: &mut impl Rng, min: f64, max: f64) -> Vec3 {
         Vec3 {
-            x: min + (max - min) * rand::random::<f64>(),
-            y: min + (max - min) * rand::random::<f64>(),
-            z: min + (max - min) * rand::random::<f64>(),
+            x: min + (max - min) * rng.gen::<f64>(),
+            y: min + (max - min) * rng.gen::<f64>(),
+            z: min + (max - min) * rng.gen::<f64>(),
         }
     }
 
-    pub fn random_in_unit_sphere() -> Vec3 {
-        loop {
-            let p = Vec3 {
-                x: 2.0 * rand::random::<f64>() - 1.0,
-                y: 2.0 * rand::random::<f64>() - 1.0,
-                z: 2.0 * rand::random::<f64>() - 1.0,
-            };
-            if p.length_squared() < 1.0 {
-                return p;
-            }
-        };
+    /// Maps a uniform sample in `[0, 1)²` to a uniformly distributed direction.
+    pub fn sample_unit_vector(u: (f64, f64)) -> Vec3 {
+        let z = 1.0 - 2.0 * u.0;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * std::f64::consts::PI * u.1;
+        Vec3 {
+            x: r * phi.cos(),
+            y: r * phi.sin(),
+            z,
+        }
     }
 
-    pub fn random_unit_vector() -> Vec3 {
-        Self::random_in_unit_sphere().unit_vector()
+    /// Maps a uniform sample in `[0, 1)²`, plus one more for the radius, to a uniform point inside the unit sphere.
+    pub fn sample_in_unit_sphere(u: (f64, f64), radius: f64) -> Vec3 {
+        radius.cbrt() * Self::sample_unit_vector(u)
     }
 
-    pub fn random_in_unit_disk() -> Vec3 {
-        loop {
-            let p = Vec3 {
-                x: 2.0 * rand::random::<f64>() -1.0,
-                y: 2.0 * rand::random::<f64>() -1.0,
-                z: 0.0,
-            };
-            if p.length_squared() < 1.0 {
-                return p;
-            }
+    /// Maps a uniform sample in `[0, 1)²` to a uniform point on the unit disk in the xy plane. Uses the
+    /// concentric mapping, which keeps neighbouring samples close together, so stratification survives.
+    pub fn sample_unit_disk(u: (f64, f64)) -> Vec3 {
+        let a = 2.0 * u.0 - 1.0;
+        let b = 2.0 * u.1 - 1.0;
+        if a == 0.0 && b == 0.0 {
+            return Vec3::new();
+        }
+        let (r, theta) = if a.abs() > b.abs() {
+            (a, std::f64::consts::FRAC_PI_4 * (b / a))
+        } else {
+            (b, std::f64::consts::FRAC_PI_2 - std::f64::consts::FRAC_PI_4 * (a / b))
+        };
+        Vec3 {
+            x: r * theta.cos(),
+            y: r * theta.sin(),
+            z: 0.0,
         }
     }
 
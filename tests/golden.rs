@@ -0,0 +1,131 @@
+//! Golden-image regression tests: every built-in scene is rendered small, with a fixed seed, and compared
+//! against the reference in `tests/golden/`. Renders are deterministic, so any difference means the output
+//! changed; the tolerance only absorbs floating point differences between platforms.
+//!
+//! After an intentional change to the output, regenerate the references with
+//! `UPDATE_GOLDEN=1 cargo test --test golden`, and look over the new images before committing them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const WIDTH: u32 = 80;
+const SAMPLES: u32 = 16;
+const SEED: u64 = 0;
+// Root mean square error allowed, in 8-bit colour values.
+const TOLERANCE: f64 = 2.0;
+// Differences are scaled up by this in the diff images so small ones are still visible.
+const DIFF_GAIN: u32 = 4;
+
+struct Ppm {
+    width: usize,
+    height: usize,
+    data: Vec<u32>,
+}
+
+impl Ppm {
+    fn parse(text: &str) -> Ppm {
+        let mut tokens = text.split_whitespace();
+        assert_eq!(tokens.next(), Some("P3"), "Not a P3 PPM!");
+        let mut next_number = || tokens.next().and_then(|token| token.parse::<u32>().ok()).expect("Truncated or malformed PPM!");
+        let width = next_number() as usize;
+        let height = next_number() as usize;
+        let _max_value = next_number();
+        let data = (0..width * height * 3).map(|_| next_number()).collect();
+        Ppm { width, height, data }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!("P3\n{} {}\n255\n", self.width, self.height);
+        for pixel in self.data.chunks(3) {
+            text.push_str(&format!("{} {} {}\n", pixel[0], pixel[1], pixel[2]));
+        }
+        text
+    }
+}
+
+fn render(scene: u32, extra_args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ray-tracing-in-one-weekend"))
+        .args([scene.to_string(), "--width".into(), WIDTH.to_string(), "--samples".into(), SAMPLES.to_string(), "--seed".into(), SEED.to_string()])
+        .args(extra_args)
+        .output()
+        .expect("Could not run the renderer!");
+    assert!(output.status.success(), "Rendering scene {} failed:\n{}", scene, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).expect("Renderer wrote something other than a PPM!")
+}
+
+fn rmse(a: &Ppm, b: &Ppm) -> f64 {
+    let sum: f64 = a.data.iter().zip(&b.data).map(|(&a, &b)| (a as f64 - b as f64).powi(2)).sum();
+    (sum / a.data.len() as f64).sqrt()
+}
+
+fn diff_image(a: &Ppm, b: &Ppm) -> Ppm {
+    Ppm {
+        width: a.width,
+        height: a.height,
+        data: a.data.iter().zip(&b.data).map(|(&a, &b)| (a.abs_diff(b) * DIFF_GAIN).min(255)).collect(),
+    }
+}
+
+fn check_scene(scene: u32) {
+    check_render(&format!("scene_{:02}", scene), scene, &[]);
+}
+
+// Renders `scene` with `extra_args` and compares it against the reference `tests/golden/<name>.ppm`.
+fn check_render(name: &str, scene: u32, extra_args: &[&str]) {
+    let reference_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.ppm", name));
+    let actual_text = render(scene, extra_args);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&reference_path, &actual_text).unwrap_or_else(|err| panic!("Could not write {}: {}", reference_path.display(), err));
+        return;
+    }
+    let reference_text = fs::read_to_string(&reference_path)
+        .unwrap_or_else(|err| panic!("Could not read {}: {}. Run with UPDATE_GOLDEN=1 to create it.", reference_path.display(), err));
+    let reference = Ppm::parse(&reference_text);
+    let actual = Ppm::parse(&actual_text);
+    assert_eq!((actual.width, actual.height), (reference.width, reference.height), "{} rendered at the wrong size!", name);
+
+    let error = rmse(&reference, &actual);
+    if error > TOLERANCE {
+        let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+        fs::create_dir_all(&out_dir).unwrap();
+        let actual_path = out_dir.join(format!("{}_actual.ppm", name));
+        let diff_path = out_dir.join(format!("{}_diff.ppm", name));
+        fs::write(&actual_path, &actual_text).unwrap();
+        fs::write(&diff_path, diff_image(&reference, &actual).to_text()).unwrap();
+        panic!(
+            "{} differs from its reference: RMSE {:.3} > {:.3}.\nRender: {}\nDiff: {}",
+            name, error, TOLERANCE, actual_path.display(), diff_path.display()
+        );
+    }
+}
+
+macro_rules! golden_tests {
+    ($($name:ident: $scene:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check_scene($scene);
+            }
+        )*
+    };
+}
+
+golden_tests! {
+    random_scene: 0,
+    two_spheres: 2,
+    two_perlin_spheres: 3,
+    earth: 4,
+    simple_light: 5,
+    cornell_box: 6,
+    cornell_smoke: 7,
+    final_scene: 8,
+    test_scene: 9,
+    triangle_scene: 10,
+    spinning_scene: 11,
+}
+
+#[test]
+fn cornell_box_spectral() {
+    check_render("scene_06_spectral", 6, &["--spectral"]);
+}